@@ -1,7 +1,10 @@
-from typing import Sequence, Literal
+from os import PathLike
+from typing import Callable, Mapping, Sequence, Literal
 
 
-class AlignmentOperation: ...
+class AlignmentOperation:
+    @property
+    def code(self) -> Literal['=', 'X', 'D', 'I', 'x', 'y']: ...
 
 
 class Match(AlignmentOperation):
@@ -65,10 +68,48 @@ class Alignment:
             operations: Sequence[AlignmentOperation],
             mode: Literal['local', 'semiglobal', 'global', 'custom'] = 'global',
     ) -> Alignment: ...
+    @classmethod
+    def from_cigar(
+            cls,
+            cigar: str,
+            x_start: int,
+            y_start: int,
+            x_len: int,
+            y_len: int,
+            mode: Literal['local', 'semiglobal', 'global', 'custom'] = 'local',
+            x: bytes | None = None,
+            y: bytes | None = None,
+            score: int = 0,
+    ) -> Alignment: ...
     def __repr__(self) -> str: ...
-    def cigar(self, hard_clip: bool) -> str: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: Alignment) -> bool: ...
+    def __ne__(self, other: Alignment) -> bool: ...
+    def __copy__(self) -> Alignment: ...
+    def __deepcopy__(self, memo: dict) -> Alignment: ...
+    def cigar(self, hard_clip: bool = False, extended: bool = True) -> str: ...
+    def format(
+            self,
+            x: bytes,
+            y: bytes,
+            width: int = 60,
+            style: Literal['emboss', 'blast'] = 'emboss',
+            scoring: Scoring | None = None,
+            x_name: str | None = None,
+            y_name: str | None = None,
+    ) -> str: ...
     def pretty(self, x: bytes, y: bytes, ncol: int) -> str: ...
     def path(self) -> tuple[int, int, AlignmentOperation]: ...
+    def x_to_y(self, pos: int) -> int | None: ...
+    def y_to_x(self, pos: int) -> int | None: ...
+    def slice_x(self, start: int, end: int) -> Alignment: ...
+    def reverse(self) -> Alignment: ...
+    def rescore(self, x: bytes, y: bytes, scoring: Scoring) -> int: ...
+    def column_scores(self, x: bytes, y: bytes, scoring: Scoring) -> list[int]: ...
+    def identity(
+            self,
+            denominator: Literal['alignment', 'matched', 'gap_compressed', 'shortest', 'x', 'y'] = 'alignment',
+    ) -> float: ...
     @property
     def score(self) -> int: ...
     @property
@@ -86,7 +127,31 @@ class Alignment:
     @property
     def operations(self) -> list[AlignmentOperation]: ...
     @property
+    def operation_codes(self) -> bytes: ...
+    def runs(self) -> list[tuple[AlignmentOperation, int]]: ...
+    @property
     def mode(self) -> str: ...
+    @property
+    def matches(self) -> int: ...
+    @property
+    def mismatches(self) -> int: ...
+    @property
+    def insertions(self) -> int: ...
+    @property
+    def deletions(self) -> int: ...
+    @property
+    def gap_opens(self) -> int: ...
+    @property
+    def aligned_length(self) -> int: ...
+    @property
+    def edit_distance(self) -> int: ...
+    @property
+    def x_coverage(self) -> float: ...
+    @property
+    def y_coverage(self) -> float: ...
+
+
+def parse_cigar(cigar: str) -> list[tuple[int, Literal['M', 'I', 'D', 'N', 'S', 'H', 'P', '=', 'X']]]: ...
 
 
 class Scoring:
@@ -98,6 +163,87 @@ class Scoring:
     ) -> Scoring: ...
     @classmethod
     def from_scores(cls, gap_open: int, gap_extend: int, match_score: int, mismatch_score: int) -> Scoring: ...
+    @classmethod
+    def from_function(cls, gap_open: int, gap_extend: int, func: Callable[[int, int], int]) -> Scoring: ...
+    @classmethod
+    def from_matrix(
+            cls,
+            gap_open: int,
+            gap_extend: int,
+            matrix: Mapping[str, Mapping[str, int]],
+            default: int | None = None,
+    ) -> Scoring: ...
+    @classmethod
+    def from_matrix_list(
+            cls,
+            gap_open: int,
+            gap_extend: int,
+            alphabet: bytes,
+            matrix: Sequence[Sequence[int]],
+            default: int | None = None,
+    ) -> Scoring: ...
+    @classmethod
+    def from_matrix_file(
+            cls,
+            gap_open: int,
+            gap_extend: int,
+            path: str | PathLike[str],
+            default: int | None = None,
+    ) -> Scoring: ...
+    def xclip(self, penalty: int) -> Scoring: ...
+    def yclip(self, penalty: int) -> Scoring: ...
+    def with_clip(
+            self,
+            xclip_prefix: int | None = None,
+            xclip_suffix: int | None = None,
+            yclip_prefix: int | None = None,
+            yclip_suffix: int | None = None,
+    ) -> Scoring: ...
+    def with_gaps(
+            self,
+            ins_gap_open: int | None = None,
+            ins_gap_extend: int | None = None,
+            del_gap_open: int | None = None,
+            del_gap_extend: int | None = None,
+    ) -> Scoring: ...
+    def with_long_gap(self, gap_open: int, gap_extend: int) -> Scoring: ...
+    def __repr__(self) -> str: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: Scoring) -> bool: ...
+    def __ne__(self, other: Scoring) -> bool: ...
+    def __copy__(self) -> Scoring: ...
+    def __deepcopy__(self, memo: dict) -> Scoring: ...
+    @property
+    def gap_open(self) -> int: ...
+    @property
+    def gap_extend(self) -> int: ...
+    @property
+    def ins_gap_open(self) -> int: ...
+    @property
+    def ins_gap_extend(self) -> int: ...
+    @property
+    def del_gap_open(self) -> int: ...
+    @property
+    def del_gap_extend(self) -> int: ...
+    @property
+    def long_gap(self) -> tuple[int, int] | None: ...
+    @property
+    def match_scores(self) -> tuple[int, int] | None: ...
+    @property
+    def xclip_prefix(self) -> int: ...
+    @property
+    def xclip_suffix(self) -> int: ...
+    @property
+    def yclip_prefix(self) -> int: ...
+    @property
+    def yclip_suffix(self) -> int: ...
+
+
+MIN_SCORE: int
+MATCH: Match
+SUBST: Subst
+DEL: Del
+INS: Ins
 
 
 DEFAULT_ALIGNER_CAPACITY: int
@@ -115,3 +261,44 @@ class PairwiseAligner:
     def calculate_global(self, x: bytes, y: bytes) -> Alignment: ...
     def calculate_semiglobal(self, x: bytes, y: bytes) -> Alignment: ...
     def calculate_local(self, x: bytes, y: bytes) -> Alignment: ...
+    def calculate_local_simd(self, x: bytes, y: bytes) -> Alignment: ...
+    def calculate_extension(
+            self,
+            x: bytes,
+            y: bytes,
+            x_seed: int,
+            y_seed: int,
+            x_drop: int,
+            seed_len: int = 0,
+    ) -> Alignment: ...
+    def score_global(self, x: bytes, y: bytes) -> tuple[int, int, int]: ...
+    def score_semiglobal(self, x: bytes, y: bytes) -> tuple[int, int, int]: ...
+    def score_local(self, x: bytes, y: bytes) -> tuple[int, int, int]: ...
+    def align_many(
+            self,
+            pairs: Sequence[tuple[bytes, bytes]],
+            mode: Literal['local', 'semiglobal', 'global', 'custom'] = 'global',
+            threads: int | None = None,
+    ) -> list[Alignment]: ...
+    def align_one_to_many(
+            self,
+            query: bytes,
+            targets: Sequence[bytes],
+            mode: Literal['local', 'semiglobal', 'global', 'custom'] = 'global',
+            threads: int | None = None,
+    ) -> list[Alignment]: ...
+
+
+class BandedAligner:
+    def __new__(
+            cls,
+            scoring: Scoring,
+            k: int,
+            w: int,
+            m: int = DEFAULT_ALIGNER_CAPACITY,
+            n: int = DEFAULT_ALIGNER_CAPACITY,
+    ) -> BandedAligner: ...
+    def calculate_custom(self, x: bytes, y: bytes) -> Alignment: ...
+    def calculate_global(self, x: bytes, y: bytes) -> Alignment: ...
+    def calculate_semiglobal(self, x: bytes, y: bytes) -> Alignment: ...
+    def calculate_local(self, x: bytes, y: bytes) -> Alignment: ...
@@ -1,4 +1,6 @@
-from typing import Optional
+from typing import Literal, Optional, Sequence, overload
+
+from . import Alignment
 
 
 def hamming(alpha: bytes, beta: bytes) -> int: ...
@@ -6,3 +8,27 @@ def simd_hamming(alpha: bytes, beta: bytes) -> int: ...
 def levenshtein(alpha: bytes, beta: bytes) -> int: ...
 def simd_levenshtein(alpha: bytes, beta: bytes) -> int: ...
 def simd_bounded_levenshtein(alpha: bytes, beta: bytes, k: int) -> Optional[int]: ...
+def levenshtein_alignment(alpha: bytes, beta: bytes) -> Alignment: ...
+def damerau_levenshtein(alpha: bytes, beta: bytes) -> int: ...
+@overload
+def pairwise_matrix(
+    seqs: Sequence[bytes],
+    metric: Literal['hamming', 'levenshtein'] = 'levenshtein',
+    max_dist: None = None,
+    threads: Optional[int] = None,
+) -> memoryview: ...
+@overload
+def pairwise_matrix(
+    seqs: Sequence[bytes],
+    metric: Literal['hamming', 'levenshtein'] = 'levenshtein',
+    *,
+    max_dist: int,
+    threads: Optional[int] = None,
+) -> tuple[memoryview, memoryview, memoryview]: ...
+def one_to_many(
+    query: bytes,
+    seqs: Sequence[bytes],
+    metric: Literal['hamming', 'levenshtein'] = 'levenshtein',
+    max_dist: Optional[int] = None,
+    threads: Optional[int] = None,
+) -> memoryview: ...
@@ -0,0 +1,49 @@
+from typing import Optional, Sequence
+
+from . import Scoring
+from ..alphabets import Alphabet
+
+
+class MultipleAlignment:
+    def __new__(
+        cls,
+        sequences: Sequence[bytes],
+        names: Optional[Sequence[str]] = None,
+        alphabet: Optional[Alphabet] = None,
+    ) -> MultipleAlignment: ...
+    def validate(self, alphabet: Alphabet) -> None: ...
+    def conservation(self) -> list[float]: ...
+    def entropy(self) -> list[float]: ...
+    def gap_fraction(self) -> list[float]: ...
+    def consensus(self, include_gaps: bool = False) -> bytes: ...
+    def iupac_consensus(self, include_gaps: bool = False) -> bytes: ...
+    def identity_matrix(self) -> list[list[float]]: ...
+    @property
+    def sequences(self) -> list[bytes]: ...
+    @property
+    def names(self) -> list[str]: ...
+    @property
+    def num_columns(self) -> int: ...
+    def column(self, index: int) -> bytes: ...
+    def ungapped(self, index: int) -> bytes: ...
+    def to_fasta(self, line_width: int = 60) -> str: ...
+    def to_clustal(self, line_width: int = 60) -> str: ...
+    def __eq__(self, other: MultipleAlignment) -> bool: ...
+    def __ne__(self, other: MultipleAlignment) -> bool: ...
+    def __len__(self) -> int: ...
+    def __getitem__(self, index: int) -> bytes: ...
+    def __repr__(self) -> str: ...
+
+
+def guide_tree(
+    sequences: Sequence[bytes],
+    scoring: Scoring,
+    names: Optional[Sequence[str]] = None,
+    threads: Optional[int] = None,
+) -> str: ...
+def progressive_align(
+    sequences: Sequence[bytes],
+    scoring: Scoring,
+    names: Optional[Sequence[str]] = None,
+    threads: Optional[int] = None,
+) -> MultipleAlignment: ...
@@ -0,0 +1,47 @@
+from typing import Optional, Sequence
+
+from . import AlignmentOperation, Scoring
+from .msa import MultipleAlignment
+
+
+class PoaAlignment:
+    @property
+    def score(self) -> int: ...
+    @property
+    def x_start(self) -> int: ...
+    @property
+    def x_end(self) -> int: ...
+    @property
+    def operations(self) -> list[AlignmentOperation]: ...
+    @property
+    def nodes(self) -> list[int]: ...
+    @property
+    def mode(self) -> str: ...
+    def path(self) -> list[tuple[Optional[int], Optional[int], AlignmentOperation]]: ...
+    def __repr__(self) -> str: ...
+
+
+class PoaGraph:
+    @property
+    def node_count(self) -> int: ...
+    @property
+    def edge_count(self) -> int: ...
+    @property
+    def nodes(self) -> bytes: ...
+    @property
+    def edges(self) -> list[tuple[int, int, int]]: ...
+    def to_dot(self) -> str: ...
+    def __repr__(self) -> str: ...
+
+
+class PoaAligner:
+    def __new__(cls, scoring: Scoring, reference: bytes) -> PoaAligner: ...
+    def calculate_custom(self, query: bytes) -> PoaAlignment: ...
+    def calculate_global(self, query: bytes) -> PoaAlignment: ...
+    def calculate_semiglobal(self, query: bytes) -> PoaAlignment: ...
+    def calculate_local(self, query: bytes) -> PoaAlignment: ...
+    def add_to_graph(self) -> None: ...
+    def consensus(self) -> bytes: ...
+    def multiple_alignment(self, names: Optional[Sequence[str]] = None) -> MultipleAlignment: ...
+    @property
+    def graph(self) -> PoaGraph: ...
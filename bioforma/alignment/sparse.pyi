@@ -0,0 +1,46 @@
+from typing import Sequence
+
+from . import Alignment, Scoring
+
+
+class SparseAlignmentResult:
+    @property
+    def path(self) -> list[int]: ...
+    @property
+    def score(self) -> int: ...
+    @property
+    def dp_vector(self) -> list[tuple[int, int]]: ...
+    def __repr__(self) -> str: ...
+
+
+def find_kmer_matches(x: bytes, y: bytes, k: int) -> list[tuple[int, int]]: ...
+def expand_kmer_matches(
+        x: bytes,
+        y: bytes,
+        k: int,
+        matches: Sequence[tuple[int, int]],
+        allowed_mismatches: int,
+) -> list[tuple[int, int]]: ...
+def lcskpp(matches: Sequence[tuple[int, int]], k: int) -> SparseAlignmentResult: ...
+def sdpkpp(
+        matches: Sequence[tuple[int, int]],
+        k: int,
+        match_score: int,
+        gap_open: int,
+        gap_extend: int,
+) -> SparseAlignmentResult: ...
+def sdpkpp_union_lcskpp_path(
+        matches: Sequence[tuple[int, int]],
+        k: int,
+        match_score: int,
+        gap_open: int,
+        gap_extend: int,
+) -> list[int]: ...
+def chain_to_alignment(
+        x: bytes,
+        y: bytes,
+        matches: Sequence[tuple[int, int]],
+        path: Sequence[int],
+        k: int,
+        scoring: Scoring,
+) -> Alignment: ...
@@ -5,6 +5,11 @@ class Alphabet:
     def __len__(self) -> int: ...
     def __and__(self, other: Alphabet) -> Alphabet: ...
     def __or__(self, other: Alphabet) -> Alphabet: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: Alphabet) -> bool: ...
+    def __ne__(self, other: Alphabet) -> bool: ...
+    def __copy__(self) -> Alphabet: ...
+    def __deepcopy__(self, memo: dict) -> Alphabet: ...
     @property
     def symbols(self) -> bytes: ...
     def is_word(self, text: bytes) -> bool: ...
@@ -13,6 +18,11 @@ class Alphabet:
 class RankTransform:
     def __new__(cls, alphabet: Alphabet) -> RankTransform: ...
     def __repr__(self) -> str: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: RankTransform) -> bool: ...
+    def __ne__(self, other: RankTransform) -> bool: ...
+    def __copy__(self) -> RankTransform: ...
+    def __deepcopy__(self, memo: dict) -> RankTransform: ...
     @property
     def ranks(self) -> dict[str, int]: ...
     def get(self, chr: bytes) -> int: ...
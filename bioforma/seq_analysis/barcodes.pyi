@@ -0,0 +1,28 @@
+from typing import Literal, Optional, Sequence
+
+
+class BarcodeCorrector:
+    def __new__(
+        cls,
+        whitelist: Sequence[bytes],
+        metric: Literal['hamming', 'levenshtein'] = 'hamming',
+        max_dist: int = 1,
+    ) -> BarcodeCorrector: ...
+    @property
+    def metric(self) -> str: ...
+    @property
+    def max_dist(self) -> int: ...
+    def correct(self, barcode: bytes) -> Optional[bytes]: ...
+    def correct_many(self, barcodes: Sequence[bytes], threads: Optional[int] = None) -> list[Optional[bytes]]: ...
+    def __len__(self) -> int: ...
+    def __contains__(self, barcode: bytes) -> bool: ...
+    def __repr__(self) -> str: ...
+
+
+def cluster_umis(
+    counts: dict[bytes, int],
+    method: Literal['adjacency', 'directional'] = 'directional',
+    metric: Literal['hamming', 'levenshtein'] = 'hamming',
+    max_dist: int = 1,
+    threads: Optional[int] = None,
+) -> list[list[bytes]]: ...
@@ -2,6 +2,7 @@ from typing import Sequence
 
 
 class Orf:
+    def __new__(cls, start: int, end: int, offset: int) -> Orf: ...
     @property
     def start(self) -> int: ...
     @property
@@ -9,6 +10,11 @@ class Orf:
     @property
     def offset(self) -> int: ...
     def __repr__(self) -> str: ...
+    def __hash__(self) -> int: ...
+    def __eq__(self, other: Orf) -> bool: ...
+    def __ne__(self, other: Orf) -> bool: ...
+    def __copy__(self) -> Orf: ...
+    def __deepcopy__(self, memo: dict) -> Orf: ...
 
 
 class Finder:
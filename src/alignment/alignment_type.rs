@@ -5,12 +5,15 @@ use bio_types::alignment::{
     AlignmentOperation  as _AlignmentOperation,
 };
 use pyo3::basic::CompareOp;
-use pyo3::exceptions::{PyNotImplementedError, PyValueError};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
+use pyo3::sync::GILOnceCell;
+use pyo3::types::{PyBytes, PyTuple, PyType};
 
-#[pyclass(subclass)]
+use crate::alignment::scoring::Scoring;
+use crate::utils::{hash, richcmp};
+
+#[pyclass(subclass, module = "bioforma.alignment")]
 pub struct AlignmentOperation(_AlignmentOperation);
 
 impl AlignmentOperation {
@@ -19,40 +22,46 @@ impl AlignmentOperation {
     }
 }
 
-#[pyclass(extends=AlignmentOperation)]
+/// Single-byte code of the operation, clips are coded regardless of their length.
+fn operation_code(operation: _AlignmentOperation) -> u8 {
+    match operation {
+        _AlignmentOperation::Match => b'=',
+        _AlignmentOperation::Subst => b'X',
+        _AlignmentOperation::Del => b'D',
+        _AlignmentOperation::Ins => b'I',
+        _AlignmentOperation::Xclip(_) => b'x',
+        _AlignmentOperation::Yclip(_) => b'y',
+    }
+}
+
+#[pymethods]
+impl AlignmentOperation {
+    /// Single-byte code of the operation: `=`, `X`, `D`, `I`, `x` or `y` for
+    /// `Match`, `Subst`, `Del`, `Ins`, `Xclip` and `Yclip`.
+    #[getter]
+    pub fn code(&self) -> char {
+        operation_code(self.0) as char
+    }
+}
+
+#[pyclass(extends=AlignmentOperation, module = "bioforma.alignment")]
 pub struct Match(_AlignmentOperation);
 
-#[pyclass(extends=AlignmentOperation)]
+#[pyclass(extends=AlignmentOperation, module = "bioforma.alignment")]
 pub struct Subst(_AlignmentOperation);
 
-#[pyclass(extends=AlignmentOperation)]
+#[pyclass(extends=AlignmentOperation, module = "bioforma.alignment")]
 pub struct Del(_AlignmentOperation);
 
-#[pyclass(extends=AlignmentOperation)]
+#[pyclass(extends=AlignmentOperation, module = "bioforma.alignment")]
 pub struct Ins(_AlignmentOperation);
 
-#[pyclass(extends=AlignmentOperation)]
+#[pyclass(extends=AlignmentOperation, module = "bioforma.alignment")]
 pub struct Xclip(_AlignmentOperation);
 
-#[pyclass(extends=AlignmentOperation)]
+#[pyclass(extends=AlignmentOperation, module = "bioforma.alignment")]
 pub struct Yclip(_AlignmentOperation);
 
-fn hash(slf: _AlignmentOperation) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    slf.hash(&mut hasher);
-    hasher.finish()
-}
-
-fn richcmp(slf: _AlignmentOperation, other: _AlignmentOperation, op: CompareOp) -> PyResult<bool> {
-    match op {
-        CompareOp::Eq => Ok(slf == other),
-        CompareOp::Ne => Ok(slf != other),
-        _ => Err(PyNotImplementedError::new_err(
-            "Operation isn't supported for this type",
-        )),
-    }
-}
-
 #[pymethods]
 impl Match {
     #[new]
@@ -64,13 +73,17 @@ impl Match {
     }
 
     fn __hash__(&self) -> u64 {
-        hash(self.0)
+        hash(&self.0)
     }
 
     fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
         richcmp(self.0, other.0, op)
     }
 
+    fn __reduce__<'p>(slf: &'p PyCell<Self>, py: Python<'p>) -> (&'p PyType, &'p PyTuple) {
+        (slf.get_type(), PyTuple::empty(py))
+    }
+
     pub fn __repr__(&self) -> String {
         "<Match>".into()
     }
@@ -87,13 +100,17 @@ impl Subst {
     }
 
     fn __hash__(&self) -> u64 {
-        hash(self.0)
+        hash(&self.0)
     }
 
     fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
         richcmp(self.0, other.0, op)
     }
 
+    fn __reduce__<'p>(slf: &'p PyCell<Self>, py: Python<'p>) -> (&'p PyType, &'p PyTuple) {
+        (slf.get_type(), PyTuple::empty(py))
+    }
+
     pub fn __repr__(&self) -> String {
         "<Subst>".into()
     }
@@ -110,13 +127,17 @@ impl Del {
     }
 
     fn __hash__(&self) -> u64 {
-        hash(self.0)
+        hash(&self.0)
     }
 
     fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
         richcmp(self.0, other.0, op)
     }
 
+    fn __reduce__<'p>(slf: &'p PyCell<Self>, py: Python<'p>) -> (&'p PyType, &'p PyTuple) {
+        (slf.get_type(), PyTuple::empty(py))
+    }
+
     pub fn __repr__(&self) -> String {
         "<Del>".into()
     }
@@ -133,13 +154,17 @@ impl Ins {
     }
 
     fn __hash__(&self) -> u64 {
-        hash(self.0)
+        hash(&self.0)
     }
 
     fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
         richcmp(self.0, other.0, op)
     }
 
+    fn __reduce__<'p>(slf: &'p PyCell<Self>, py: Python<'p>) -> (&'p PyType, &'p PyTuple) {
+        (slf.get_type(), PyTuple::empty(py))
+    }
+
     pub fn __repr__(&self) -> String {
         "<Ins>".into()
     }
@@ -156,13 +181,20 @@ impl Xclip {
     }
 
     fn __hash__(&self) -> u64 {
-        hash(self.0)
+        hash(&self.0)
     }
 
     fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
         richcmp(self.0, other.0, op)
     }
 
+    fn __reduce__(slf: &PyCell<Self>) -> Option<(&PyType, (usize,))> {
+        match slf.borrow().0 {
+            _AlignmentOperation::Xclip(x) => Some((slf.get_type(), (x,))),
+            _ => None,
+        }
+    }
+
     pub fn __repr__(&self) -> Option<String> {
         match self.0 {
             _AlignmentOperation::Xclip(x) => Some(format!("<Xclip: x={}>", x)),
@@ -182,13 +214,20 @@ impl Yclip {
     }
 
     fn __hash__(&self) -> u64 {
-        hash(self.0)
+        hash(&self.0)
     }
 
     fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
         richcmp(self.0, other.0, op)
     }
 
+    fn __reduce__(slf: &PyCell<Self>) -> Option<(&PyType, (usize,))> {
+        match slf.borrow().0 {
+            _AlignmentOperation::Yclip(y) => Some((slf.get_type(), (y,))),
+            _ => None,
+        }
+    }
+
     pub fn __repr__(&self) -> Option<String> {
         match self.0 {
             _AlignmentOperation::Yclip(y) => Some(format!("<Yclip: y={}>", y)),
@@ -197,15 +236,32 @@ impl Yclip {
     }
 }
 
-fn rust_bio_alignment_operation_into_py_object(
+static MATCH: GILOnceCell<PyObject> = GILOnceCell::new();
+static SUBST: GILOnceCell<PyObject> = GILOnceCell::new();
+static DEL: GILOnceCell<PyObject> = GILOnceCell::new();
+static INS: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// Return the shared instance of the operation without arguments, so long alignments
+/// don't create an object per operation.
+fn singleton<T: pyo3::PyClass>(
+    cell: &'static GILOnceCell<PyObject>,
+    init: impl FnOnce() -> PyClassInitializer<T>,
+    py: Python,
+) -> Option<PyObject> {
+    cell.get_or_try_init(py, || Py::new(py, init()).map(|o| o.to_object(py)))
+        .ok()
+        .map(|o| o.clone_ref(py))
+}
+
+pub fn rust_bio_alignment_operation_into_py_object(
     _operation: _AlignmentOperation,
     py: Python,
 ) -> Option<PyObject> {
     match _operation {
-        _AlignmentOperation::Match => PyCell::new(py, Match::new()).ok().map(|o| o.to_object(py)),
-        _AlignmentOperation::Subst => PyCell::new(py, Subst::new()).ok().map(|o| o.to_object(py)),
-        _AlignmentOperation::Del => PyCell::new(py, Del::new()).ok().map(|o| o.to_object(py)),
-        _AlignmentOperation::Ins => PyCell::new(py, Ins::new()).ok().map(|o| o.to_object(py)),
+        _AlignmentOperation::Match => singleton(&MATCH, || Match::new().into(), py),
+        _AlignmentOperation::Subst => singleton(&SUBST, || Subst::new().into(), py),
+        _AlignmentOperation::Del => singleton(&DEL, || Del::new().into(), py),
+        _AlignmentOperation::Ins => singleton(&INS, || Ins::new().into(), py),
         _AlignmentOperation::Xclip(x) => {
             PyCell::new(py, Xclip::new(x)).ok().map(|o| o.to_object(py))
         }
@@ -215,9 +271,345 @@ fn rust_bio_alignment_operation_into_py_object(
     }
 }
 
-#[pyclass]
+#[derive(Clone)]
+#[pyclass(module = "bioforma.alignment")]
 pub struct Alignment(pub _Alignment);
 
+pub fn parse_alignment_mode(mode: &str) -> PyResult<_AlignmentMode> {
+    match mode {
+        "local" => Ok(_AlignmentMode::Local),
+        "semiglobal" => Ok(_AlignmentMode::Semiglobal),
+        "global" => Ok(_AlignmentMode::Global),
+        "custom" => Ok(_AlignmentMode::Custom),
+        _ => Err(PyValueError::new_err(format!(
+            "{} can't be used as the mode",
+            mode
+        ))),
+    }
+}
+
+/// Counts of the alignment operations, excluding clips.
+#[derive(Default)]
+struct OperationCounts {
+    matches: usize,
+    mismatches: usize,
+    insertions: usize,
+    deletions: usize,
+    gap_opens: usize,
+}
+
+impl Alignment {
+    fn count_operations(&self) -> OperationCounts {
+        let mut counts = OperationCounts::default();
+        let mut last = None;
+        for &operation in self.0.operations.iter() {
+            match operation {
+                _AlignmentOperation::Match => counts.matches += 1,
+                _AlignmentOperation::Subst => counts.mismatches += 1,
+                _AlignmentOperation::Ins => counts.insertions += 1,
+                _AlignmentOperation::Del => counts.deletions += 1,
+                _AlignmentOperation::Xclip(_) | _AlignmentOperation::Yclip(_) => continue,
+            }
+            let is_gap = matches!(
+                operation,
+                _AlignmentOperation::Ins | _AlignmentOperation::Del
+            );
+            if is_gap && last != Some(operation) {
+                counts.gap_opens += 1;
+            }
+            last = Some(operation);
+        }
+        counts
+    }
+}
+
+impl Alignment {
+    /// Return the positions in x and y where every aligned operation starts, skipping clips.
+    fn columns(&self) -> Vec<(usize, usize, _AlignmentOperation)> {
+        let (mut i, mut j) = if self.0.mode == _AlignmentMode::Custom {
+            (0, 0)
+        } else {
+            (self.0.xstart, self.0.ystart)
+        };
+        let mut columns = Vec::with_capacity(self.0.operations.len());
+        for &operation in self.0.operations.iter() {
+            match operation {
+                _AlignmentOperation::Xclip(len) => i += len,
+                _AlignmentOperation::Yclip(len) => j += len,
+                _ => {
+                    columns.push((i, j, operation));
+                    if operation != _AlignmentOperation::Del {
+                        i += 1;
+                    }
+                    if operation != _AlignmentOperation::Ins {
+                        j += 1;
+                    }
+                }
+            }
+        }
+        columns
+    }
+}
+
+impl Alignment {
+    /// Score every operation with `scoring`. Every column of a gap takes the increase of
+    /// the gap score, so the first one takes both gap open and gap extend penalties.
+    /// Clips are only penalized in custom mode. In the other modes they are implicit
+    /// and free like in `PairwiseAligner`.
+    fn score_columns(&self, x: &[u8], y: &[u8], scoring: &Scoring) -> PyResult<Vec<i32>> {
+        let out_of_sequences = || PyValueError::new_err("Alignment is out of the sequences");
+        let custom = self.0.mode == _AlignmentMode::Custom;
+        let (mut i, mut j) = if custom {
+            (0, 0)
+        } else {
+            (self.0.xstart, self.0.ystart)
+        };
+
+        let mut scores = Vec::with_capacity(self.0.operations.len());
+        let mut last = None;
+        let mut gap_len = 0;
+        let mut aligned = false;
+        for &operation in self.0.operations.iter() {
+            gap_len = if last == Some(operation) {
+                gap_len + 1
+            } else {
+                1
+            };
+            let mut score = 0;
+            match operation {
+                _AlignmentOperation::Match | _AlignmentOperation::Subst => {
+                    match (x.get(i), y.get(j)) {
+                        (Some(&a), Some(&b)) => score = (scoring.match_fn)(a, b),
+                        _ => return Err(out_of_sequences()),
+                    }
+                    i += 1;
+                    j += 1;
+                }
+                _AlignmentOperation::Ins => {
+                    score = scoring.ins_gap(gap_len) - scoring.ins_gap(gap_len - 1);
+                    i += 1;
+                }
+                _AlignmentOperation::Del => {
+                    score = scoring.del_gap(gap_len) - scoring.del_gap(gap_len - 1);
+                    j += 1;
+                }
+                _AlignmentOperation::Xclip(len) => {
+                    if custom {
+                        score = if aligned {
+                            scoring.xclip_suffix
+                        } else {
+                            scoring.xclip_prefix
+                        };
+                    }
+                    i += len;
+                }
+                _AlignmentOperation::Yclip(len) => {
+                    if custom {
+                        score = if aligned {
+                            scoring.yclip_suffix
+                        } else {
+                            scoring.yclip_prefix
+                        };
+                    }
+                    j += len;
+                }
+            }
+            if i > x.len() || j > y.len() {
+                return Err(out_of_sequences());
+            }
+            aligned |= !matches!(
+                operation,
+                _AlignmentOperation::Xclip(_) | _AlignmentOperation::Yclip(_)
+            );
+            last = Some(operation);
+            scores.push(score);
+        }
+        Ok(scores)
+    }
+}
+
+/// Layout of `Alignment.format` output.
+#[derive(Clone, Copy, PartialEq)]
+enum FormatStyle {
+    Emboss,
+    Blast,
+}
+
+impl Alignment {
+    /// Render the aligned region as blocks of `width` columns. The middle line marks
+    /// identical, similar (positive score under `scoring`) and mismatched columns.
+    fn format_blocks(
+        &self,
+        x: &[u8],
+        y: &[u8],
+        width: usize,
+        style: FormatStyle,
+        scoring: Option<&Scoring>,
+        names: (&str, &str),
+    ) -> PyResult<String> {
+        let columns = self.columns();
+        let mut rows = (String::new(), String::new(), String::new());
+        let (mut identities, mut positives, mut gaps) = (0, 0, 0);
+        for &(i, j, operation) in columns.iter() {
+            let a = (operation != _AlignmentOperation::Del).then(|| x.get(i));
+            let b = (operation != _AlignmentOperation::Ins).then(|| y.get(j));
+            let (a, b) = match (a, b) {
+                (Some(None), _) | (_, Some(None)) => {
+                    return Err(PyValueError::new_err("Alignment is out of the sequences"))
+                }
+                (a, b) => (a.flatten().copied(), b.flatten().copied()),
+            };
+            let symbol = match (a, b) {
+                (Some(a), Some(b)) if a == b => {
+                    identities += 1;
+                    positives += 1;
+                    match style {
+                        FormatStyle::Emboss => '|',
+                        FormatStyle::Blast => a as char,
+                    }
+                }
+                (Some(a), Some(b)) if scoring.is_some_and(|s| (s.match_fn)(a, b) > 0) => {
+                    positives += 1;
+                    match style {
+                        FormatStyle::Emboss => ':',
+                        FormatStyle::Blast => '+',
+                    }
+                }
+                (Some(_), Some(_)) => match style {
+                    FormatStyle::Emboss => '.',
+                    FormatStyle::Blast => ' ',
+                },
+                _ => {
+                    gaps += 1;
+                    ' '
+                }
+            };
+            rows.0.push(a.map_or('-', char::from));
+            rows.1.push(symbol);
+            rows.2.push(b.map_or('-', char::from));
+        }
+
+        let length = columns.len();
+        let percent = |count: usize| 100.0 * fraction(count, length);
+        let mut output = match style {
+            FormatStyle::Emboss => format!(
+                "# 1: {}\n# 2: {}\n# Length: {length}\n\
+                 # Identity:   {identities}/{length} ({:.1}%)\n\
+                 # Similarity: {positives}/{length} ({:.1}%)\n\
+                 # Gaps:       {gaps}/{length} ({:.1}%)\n\
+                 # Score: {}\n",
+                names.0,
+                names.1,
+                percent(identities),
+                percent(positives),
+                percent(gaps),
+                self.0.score,
+            ),
+            FormatStyle::Blast => format!(
+                " Score = {}, Identities = {identities}/{length} ({:.0}%), \
+                 Positives = {positives}/{length} ({:.0}%), Gaps = {gaps}/{length} ({:.0}%)\n",
+                self.0.score,
+                percent(identities),
+                percent(positives),
+                percent(gaps),
+            ),
+        };
+
+        let name_width = names.0.len().max(names.1.len());
+        let number_width = x.len().max(y.len()).to_string().len();
+        let (mut x_pos, mut y_pos) = columns.first().map_or((0, 0), |&(i, j, _)| (i, j));
+        let row = |name: &str, from: usize, to: usize, sequence: &str| match style {
+            FormatStyle::Emboss => {
+                format!(
+                    "{name:<name_width$} {from:>number_width$} {sequence} {to:>number_width$}\n"
+                )
+            }
+            FormatStyle::Blast => {
+                format!("{name:<name_width$}  {from:<number_width$}  {sequence}  {to}\n")
+            }
+        };
+        let chars: Vec<Vec<char>> = vec![
+            rows.0.chars().collect(),
+            rows.1.chars().collect(),
+            rows.2.chars().collect(),
+        ];
+        for block in (0..length).step_by(width) {
+            let block_end = (block + width).min(length);
+            let top: String = chars[0][block..block_end].iter().collect();
+            let middle: String = chars[1][block..block_end].iter().collect();
+            let bottom: String = chars[2][block..block_end].iter().collect();
+            let x_count = top.chars().filter(|&c| c != '-').count();
+            let y_count = bottom.chars().filter(|&c| c != '-').count();
+            // Coordinates are 1-based, a block without residues shows the previous one
+            let x_from = if x_count > 0 { x_pos + 1 } else { x_pos };
+            let y_from = if y_count > 0 { y_pos + 1 } else { y_pos };
+            x_pos += x_count;
+            y_pos += y_count;
+
+            let indent = match style {
+                FormatStyle::Emboss => name_width + number_width + 2,
+                FormatStyle::Blast => name_width + number_width + 4,
+            };
+            output.push('\n');
+            output.push_str(&row(names.0, x_from, x_pos, &top));
+            output.push_str(&format!("{}{}\n", " ".repeat(indent), middle.trim_end()));
+            output.push_str(&row(names.1, y_from, y_pos, &bottom));
+        }
+        Ok(output)
+    }
+}
+
+fn fraction(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Split the CIGAR string into `(length, operation)` runs. `*` (unavailable CIGAR in SAM)
+/// is parsed as no operations.
+#[pyfunction]
+pub fn parse_cigar(cigar: &str) -> PyResult<Vec<(usize, char)>> {
+    let mut runs = Vec::new();
+    if cigar == "*" {
+        return Ok(runs);
+    }
+
+    let mut length: Option<usize> = None;
+    for c in cigar.chars() {
+        match c {
+            '0'..='9' => {
+                let digit = c as usize - '0' as usize;
+                length = length
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|length| length.checked_add(digit));
+                if length.is_none() {
+                    return Err(PyValueError::new_err("CIGAR operation is too long"));
+                }
+            }
+            'M' | 'I' | 'D' | 'N' | 'S' | 'H' | 'P' | '=' | 'X' => match length.take() {
+                Some(0) | None => {
+                    return Err(PyValueError::new_err(format!(
+                        "CIGAR operation {c:?} must have a positive length"
+                    )))
+                }
+                Some(length) => runs.push((length, c)),
+            },
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Incorrect CIGAR operation {c:?}"
+                )))
+            }
+        }
+    }
+    if length.is_some() {
+        return Err(PyValueError::new_err("CIGAR can't end with a length"));
+    }
+    Ok(runs)
+}
+
 #[pymethods]
 impl Alignment {
     #[new]
@@ -239,16 +631,7 @@ impl Alignment {
             .map(|o| o.borrow().get_operation())
             .collect();
 
-        let _mode: PyResult<_AlignmentMode> = match mode {
-            "local" => Ok(_AlignmentMode::Local),
-            "semiglobal" => Ok(_AlignmentMode::Semiglobal),
-            "global" => Ok(_AlignmentMode::Global),
-            "custom" => Ok(_AlignmentMode::Custom),
-            _ => Err(PyValueError::new_err(format!(
-                "{} can't be used as the mode",
-                mode
-            ))),
-        };
+        let _mode = parse_alignment_mode(mode);
 
         Ok(Alignment(_Alignment {
             score,
@@ -263,6 +646,120 @@ impl Alignment {
         }))
     }
 
+    /// Build the alignment from a CIGAR string starting at `x_start` and `y_start`. Clips
+    /// (`S` and `H` are treated the same way) move the start and the end of the alignment
+    /// in x, `N` is represented with deletions and `P` is skipped. `M` is a match unless
+    /// `x` and `y` are given to tell matches from substitutions.
+    #[classmethod]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (cigar, x_start, y_start, x_len, y_len, mode="local", x=None, y=None, score=0))]
+    pub fn from_cigar(
+        _cls: &PyType,
+        cigar: &str,
+        x_start: usize,
+        y_start: usize,
+        x_len: usize,
+        y_len: usize,
+        mode: &str,
+        x: Option<&[u8]>,
+        y: Option<&[u8]>,
+        score: i32,
+    ) -> PyResult<Self> {
+        let mode = parse_alignment_mode(mode)?;
+        let runs = parse_cigar(cigar)?;
+        let out_of_sequences = || PyValueError::new_err("CIGAR is out of the sequences");
+
+        let mut operations = Vec::new();
+        let (mut x_i, mut y_i) = (x_start, y_start);
+        let (mut xstart, mut xend) = (None, None);
+        let mut suffix_clip = 0;
+        for &(length, op) in runs.iter() {
+            if xend.is_some() && op != 'S' && op != 'H' {
+                return Err(PyValueError::new_err(
+                    "CIGAR clips are only allowed at the ends",
+                ));
+            }
+            match op {
+                'S' | 'H' => {
+                    if xstart.is_none() {
+                        x_i += length;
+                    } else {
+                        xend = xend.or(Some(x_i));
+                        suffix_clip += length;
+                    }
+                    continue;
+                }
+                'P' => continue,
+                _ => {}
+            }
+            xstart = xstart.or(Some(x_i));
+
+            for _ in 0..length {
+                let operation = match op {
+                    'M' | '=' | 'X' => {
+                        let operation = match (op, x, y) {
+                            ('X', _, _) => _AlignmentOperation::Subst,
+                            ('M', Some(x), Some(y)) => match (x.get(x_i), y.get(y_i)) {
+                                (Some(a), Some(b)) if a != b => _AlignmentOperation::Subst,
+                                (Some(_), Some(_)) => _AlignmentOperation::Match,
+                                _ => return Err(out_of_sequences()),
+                            },
+                            _ => _AlignmentOperation::Match,
+                        };
+                        x_i += 1;
+                        y_i += 1;
+                        operation
+                    }
+                    'I' => {
+                        x_i += 1;
+                        _AlignmentOperation::Ins
+                    }
+                    _ => {
+                        y_i += 1;
+                        _AlignmentOperation::Del
+                    }
+                };
+                operations.push(operation);
+            }
+        }
+
+        let xstart = xstart.unwrap_or(x_i);
+        let xend = xend.unwrap_or(x_i);
+        if xend + suffix_clip > x_len || y_i > y_len {
+            return Err(out_of_sequences());
+        }
+
+        if let _AlignmentMode::Custom = mode {
+            // Clips are explicit in custom mode
+            let mut prefix = Vec::new();
+            if y_start > 0 {
+                prefix.push(_AlignmentOperation::Yclip(y_start));
+            }
+            if xstart > 0 {
+                prefix.push(_AlignmentOperation::Xclip(xstart));
+            }
+            operations.splice(0..0, prefix);
+            if x_len > xend {
+                operations.push(_AlignmentOperation::Xclip(x_len - xend));
+            }
+            if y_len > y_i {
+                operations.push(_AlignmentOperation::Yclip(y_len - y_i));
+            }
+        }
+
+        Ok(Alignment(_Alignment {
+            score,
+            xstart,
+            ystart: y_start,
+            xend,
+            yend: y_i,
+            xlen: x_len,
+            ylen: y_len,
+            operations,
+            mode,
+        }))
+    }
+
     #[getter]
     pub fn score(&self) -> i32 {
         self.0.score
@@ -309,8 +806,40 @@ impl Alignment {
             .collect()
     }
 
+    /// Operations as bytes with one code per operation (see `AlignmentOperation.code`),
+    /// usable with `array` or `numpy.frombuffer` without creating operation objects.
     #[getter]
-    pub fn mode(&self) -> &str {
+    pub fn operation_codes<'p>(&self, py: Python<'p>) -> &'p PyBytes {
+        let codes: Vec<u8> = self
+            .0
+            .operations
+            .iter()
+            .map(|&o| operation_code(o))
+            .collect();
+        PyBytes::new(py, &codes)
+    }
+
+    /// Run-length encoded operations as `(operation, count)` pairs.
+    pub fn runs(&self, py: Python) -> Vec<(Option<PyObject>, usize)> {
+        let mut runs: Vec<(_AlignmentOperation, usize)> = Vec::new();
+        for &operation in self.0.operations.iter() {
+            match runs.last_mut() {
+                Some((last, count)) if *last == operation => *count += 1,
+                _ => runs.push((operation, 1)),
+            }
+        }
+        runs.into_iter()
+            .map(|(operation, count)| {
+                (
+                    rust_bio_alignment_operation_into_py_object(operation, py),
+                    count,
+                )
+            })
+            .collect()
+    }
+
+    #[getter]
+    pub fn mode(&self) -> &'static str {
         match self.0.mode {
             _AlignmentMode::Local => "local",
             _AlignmentMode::Semiglobal => "semiglobal",
@@ -319,21 +848,251 @@ impl Alignment {
         }
     }
 
-    pub fn cigar(&self, hard_clip: bool) -> PyResult<String> {
-        match self.0.mode {
-            _AlignmentMode::Global => {
-                return Err(PyValueError::new_err(
-                    "Cigar is not supported for Global Alignment mode",
-                ))
+    #[getter]
+    pub fn matches(&self) -> usize {
+        self.count_operations().matches
+    }
+
+    #[getter]
+    pub fn mismatches(&self) -> usize {
+        self.count_operations().mismatches
+    }
+
+    #[getter]
+    pub fn insertions(&self) -> usize {
+        self.count_operations().insertions
+    }
+
+    #[getter]
+    pub fn deletions(&self) -> usize {
+        self.count_operations().deletions
+    }
+
+    /// Number of gaps, where consecutive insertions or deletions make up one gap.
+    #[getter]
+    pub fn gap_opens(&self) -> usize {
+        self.count_operations().gap_opens
+    }
+
+    /// Number of alignment columns, i.e. all operations except clips.
+    #[getter]
+    pub fn aligned_length(&self) -> usize {
+        let counts = self.count_operations();
+        counts.matches + counts.mismatches + counts.insertions + counts.deletions
+    }
+
+    /// Number of mismatches, inserted and deleted bases, like the NM tag in SAM.
+    #[getter]
+    pub fn edit_distance(&self) -> usize {
+        let counts = self.count_operations();
+        counts.mismatches + counts.insertions + counts.deletions
+    }
+
+    /// Fraction of x covered by the alignment.
+    #[getter]
+    pub fn x_coverage(&self) -> f64 {
+        fraction(self.0.xend.saturating_sub(self.0.xstart), self.0.xlen)
+    }
+
+    /// Fraction of y covered by the alignment.
+    #[getter]
+    pub fn y_coverage(&self) -> f64 {
+        fraction(self.0.yend.saturating_sub(self.0.ystart), self.0.ylen)
+    }
+
+    /// Fraction of identical bases. The denominator is chosen by `denominator`:
+    /// - `alignment`: the number of alignment columns,
+    /// - `matched`: the number of matches and mismatches, so gaps are ignored,
+    /// - `gap_compressed`: matches, mismatches and gap opens, so every gap counts once,
+    /// - `shortest`: the length of the shorter sequence,
+    /// - `x` or `y`: the length of the sequence.
+    #[pyo3(signature = (denominator="alignment"))]
+    pub fn identity(&self, denominator: &str) -> PyResult<f64> {
+        let counts = self.count_operations();
+        let total = match denominator {
+            "alignment" => {
+                counts.matches + counts.mismatches + counts.insertions + counts.deletions
             }
-            _AlignmentMode::Local => {
-                return Err(PyValueError::new_err(
-                    "Cigar is not supported for Local Alignment mode",
-                ))
+            "matched" => counts.matches + counts.mismatches,
+            "gap_compressed" => counts.matches + counts.mismatches + counts.gap_opens,
+            "shortest" => self.0.xlen.min(self.0.ylen),
+            "x" => self.0.xlen,
+            "y" => self.0.ylen,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "{denominator} can't be used as the identity denominator"
+                )))
             }
-            _ => {}
+        };
+        Ok(fraction(counts.matches, total))
+    }
+
+    /// Compute the score of the alignment operations under `scoring`.
+    pub fn rescore(&self, x: &[u8], y: &[u8], scoring: &Scoring) -> PyResult<i32> {
+        Ok(self.score_columns(x, y, scoring)?.into_iter().sum())
+    }
+
+    /// Compute the score contribution of every operation under `scoring`.
+    pub fn column_scores(&self, x: &[u8], y: &[u8], scoring: &Scoring) -> PyResult<Vec<i32>> {
+        self.score_columns(x, y, scoring)
+    }
+
+    /// Return the position in y aligned to the position `pos` of x, or `None` if it's
+    /// inserted or isn't covered by the alignment.
+    pub fn x_to_y(&self, pos: usize) -> PyResult<Option<usize>> {
+        if pos >= self.0.xlen {
+            return Err(PyValueError::new_err("pos is out of x"));
+        }
+        Ok(self
+            .columns()
+            .into_iter()
+            .find_map(|(i, j, operation)| match operation {
+                _AlignmentOperation::Match | _AlignmentOperation::Subst if i == pos => Some(j),
+                _ => None,
+            }))
+    }
+
+    /// Return the position in x aligned to the position `pos` of y, or `None` if it's
+    /// deleted or isn't covered by the alignment.
+    pub fn y_to_x(&self, pos: usize) -> PyResult<Option<usize>> {
+        if pos >= self.0.ylen {
+            return Err(PyValueError::new_err("pos is out of y"));
+        }
+        Ok(self
+            .columns()
+            .into_iter()
+            .find_map(|(i, j, operation)| match operation {
+                _AlignmentOperation::Match | _AlignmentOperation::Subst if j == pos => Some(i),
+                _ => None,
+            }))
+    }
+
+    /// Cut the alignment to the part covering `x[start..end]`. Deletions are kept only
+    /// between the kept positions of x. The result is a local alignment, and its score is
+    /// set to zero, use `rescore` to compute it.
+    pub fn slice_x(&self, start: usize, end: usize) -> PyResult<Self> {
+        if start >= end {
+            return Err(PyValueError::new_err("start must be less than end"));
         }
-        Ok(self.0.cigar(hard_clip))
+        let columns = self.columns();
+        let in_slice = |&(i, _, operation): &(usize, usize, _AlignmentOperation)| {
+            operation != _AlignmentOperation::Del && i >= start && i < end
+        };
+        let (first, last) = match (
+            columns.iter().position(in_slice),
+            columns.iter().rposition(in_slice),
+        ) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(PyValueError::new_err("slice doesn't overlap the alignment")),
+        };
+
+        let (xstart, ystart, _) = columns[first];
+        let (mut xend, mut yend, last_operation) = columns[last];
+        xend += 1;
+        if last_operation != _AlignmentOperation::Ins {
+            yend += 1;
+        }
+        Ok(Alignment(_Alignment {
+            score: 0,
+            xstart,
+            ystart,
+            xend,
+            yend,
+            xlen: self.0.xlen,
+            ylen: self.0.ylen,
+            operations: columns[first..=last].iter().map(|&(_, _, o)| o).collect(),
+            mode: _AlignmentMode::Local,
+        }))
+    }
+
+    /// Reverse the alignment, so it aligns the reversed sequences. Combined with the
+    /// reverse complement of both sequences it gives the alignment of the other strand.
+    pub fn reverse(&self) -> Self {
+        Alignment(_Alignment {
+            score: self.0.score,
+            xstart: self.0.xlen.saturating_sub(self.0.xend),
+            ystart: self.0.ylen.saturating_sub(self.0.yend),
+            xend: self.0.xlen.saturating_sub(self.0.xstart),
+            yend: self.0.ylen.saturating_sub(self.0.ystart),
+            xlen: self.0.xlen,
+            ylen: self.0.ylen,
+            operations: self.0.operations.iter().rev().copied().collect(),
+            mode: self.0.mode,
+        })
+    }
+
+    /// Return the CIGAR string of the alignment. Unaligned prefix and suffix of x are
+    /// clipped, matches and substitutions are written as `=` and `X` if `extended`
+    /// and as `M` otherwise.
+    #[pyo3(signature = (hard_clip=false, extended=true))]
+    pub fn cigar(&self, hard_clip: bool, extended: bool) -> String {
+        let mut cigar = String::new();
+        if self.0.operations.is_empty() {
+            return cigar;
+        }
+
+        let clip = if hard_clip { 'H' } else { 'S' };
+        let mut runs: Vec<(usize, char)> = Vec::new();
+        if self.0.xstart > 0 {
+            runs.push((self.0.xstart, clip));
+        }
+        for operation in self.0.operations.iter() {
+            let op = match operation {
+                _AlignmentOperation::Match if extended => '=',
+                _AlignmentOperation::Subst if extended => 'X',
+                _AlignmentOperation::Match | _AlignmentOperation::Subst => 'M',
+                _AlignmentOperation::Del => 'D',
+                _AlignmentOperation::Ins => 'I',
+                _AlignmentOperation::Xclip(_) | _AlignmentOperation::Yclip(_) => continue,
+            };
+            match runs.last_mut() {
+                Some((length, last)) if *last == op => *length += 1,
+                _ => runs.push((1, op)),
+            }
+        }
+        if self.0.xlen > self.0.xend {
+            runs.push((self.0.xlen - self.0.xend, clip));
+        }
+
+        for (length, op) in runs {
+            cigar.push_str(&format!("{length}{op}"));
+        }
+        cigar
+    }
+
+    /// Format the aligned region for reports with `width` columns per line and 1-based
+    /// coordinates at both ends of each line. `style` is `emboss` (`|` identical, `:` similar,
+    /// `.` mismatch) or `blast` (identical letters and `+` for similar). Similar columns are
+    /// marked only if `scoring` is given.
+    #[pyo3(signature = (x, y, width=60, style="emboss", scoring=None, x_name=None, y_name=None))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn format(
+        &self,
+        x: &[u8],
+        y: &[u8],
+        width: usize,
+        style: &str,
+        scoring: Option<&Scoring>,
+        x_name: Option<&str>,
+        y_name: Option<&str>,
+    ) -> PyResult<String> {
+        if width == 0 {
+            return Err(PyValueError::new_err("width must be positive"));
+        }
+        let (style, default_names) = match style {
+            "emboss" => (FormatStyle::Emboss, ("x", "y")),
+            "blast" => (FormatStyle::Blast, ("Query", "Sbjct")),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "{style} can't be used as the style"
+                )))
+            }
+        };
+        let names = (
+            x_name.unwrap_or(default_names.0),
+            y_name.unwrap_or(default_names.1),
+        );
+        self.format_blocks(x, y, width, style, scoring, names)
     }
 
     pub fn pretty(&self, x: &[u8], y: &[u8], ncol: usize) -> String {
@@ -352,6 +1111,65 @@ impl Alignment {
             .collect()
     }
 
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'p>(
+        slf: &'p PyCell<Self>,
+        py: Python<'p>,
+    ) -> (
+        &'p PyType,
+        (
+            i32,
+            usize,
+            usize,
+            usize,
+            usize,
+            usize,
+            usize,
+            Vec<Option<PyObject>>,
+            &'static str,
+        ),
+    ) {
+        let alignment = slf.borrow();
+        let a = &alignment.0;
+        (
+            slf.get_type(),
+            (
+                a.score,
+                a.xstart,
+                a.ystart,
+                a.xend,
+                a.yend,
+                a.xlen,
+                a.ylen,
+                alignment.operations(py),
+                alignment.mode(),
+            ),
+        )
+    }
+
+    fn __hash__(&self) -> u64 {
+        let a = &self.0;
+        hash(&(
+            a.score,
+            (a.xstart, a.ystart, a.xend, a.yend),
+            (a.xlen, a.ylen),
+            &a.operations,
+            self.mode(),
+        ))
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
+        richcmp(&self.0, &other.0, op)
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        self.clone()
+    }
+
     pub fn __repr__(&self) -> String {
         format!(
             "<Alignment: score={}, x_start={}, y_start={}, x_end={}, y_end={}, x_len={}, y_len={}, operations={:?}, mode={:?}>",
@@ -0,0 +1,86 @@
+#[rustfmt::skip]
+use bio_types::alignment::{
+    Alignment           as _Alignment,
+    AlignmentMode       as _AlignmentMode,
+};
+use bio::alignment::pairwise::banded::Aligner as _BandedAligner;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::alignment::alignment_type::Alignment;
+use crate::alignment::scoring::{BoxedMatchFn, PairwiseAligner, Scoring, DEFAULT_ALIGNER_CAPACITY};
+
+/// Banded pairwise aligner. The band is built around the k-mer matches between x and y
+/// (of length `k`) extended by the window of size `w`, and the dynamic programming is
+/// done only inside of this band.
+#[pyclass]
+pub struct BandedAligner {
+    aligner: _BandedAligner<BoxedMatchFn>,
+    scoring: Scoring,
+    k: usize,
+}
+
+impl BandedAligner {
+    fn align(&mut self, x: &[u8], y: &[u8], mode: _AlignmentMode) -> PyResult<_Alignment> {
+        // rust-bio can't build the band without k-mers in both sequences, so short
+        // sequences are aligned with the full dynamic programming matrix
+        if x.len() < self.k || y.len() < self.k {
+            return PairwiseAligner::new(self.scoring.clone(), x.len(), y.len()).align(x, y, mode);
+        }
+        Ok(match mode {
+            _AlignmentMode::Custom => self.aligner.custom(x, y),
+            _AlignmentMode::Global => self.aligner.global(x, y),
+            _AlignmentMode::Semiglobal => self.aligner.semiglobal(x, y),
+            _AlignmentMode::Local => self.aligner.local(x, y),
+        })
+    }
+}
+
+#[pymethods]
+impl BandedAligner {
+    #[new]
+    #[pyo3(signature = (scoring, k, w, m=DEFAULT_ALIGNER_CAPACITY, n=DEFAULT_ALIGNER_CAPACITY))]
+    pub fn new(scoring: Scoring, k: usize, w: usize, m: usize, n: usize) -> PyResult<Self> {
+        if k == 0 {
+            return Err(PyValueError::new_err("k must be positive"));
+        }
+
+        if !scoring.has_plain_gaps() {
+            return Err(PyValueError::new_err(
+                "BandedAligner supports only the same affine gap penalties for insertions and deletions",
+            ));
+        }
+
+        Ok(BandedAligner {
+            aligner: _BandedAligner::with_capacity_and_scoring(
+                m,
+                n,
+                scoring.to_rust_bio_scoring(),
+                k,
+                w,
+            ),
+            scoring,
+            k,
+        })
+    }
+
+    pub fn calculate_custom(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align(x, y, _AlignmentMode::Custom))
+            .map(Alignment)
+    }
+
+    pub fn calculate_global(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align(x, y, _AlignmentMode::Global))
+            .map(Alignment)
+    }
+
+    pub fn calculate_semiglobal(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align(x, y, _AlignmentMode::Semiglobal))
+            .map(Alignment)
+    }
+
+    pub fn calculate_local(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align(x, y, _AlignmentMode::Local))
+            .map(Alignment)
+    }
+}
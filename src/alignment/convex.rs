@@ -0,0 +1,181 @@
+// Global, semiglobal and local alignment with dual-affine gaps (Gotoh's algorithm with
+// two gap penalty pairs per direction, as in minimap2): a gap of length `l` scores
+// `max(o1 + e1 * l, o2 + e2 * l)`, a piecewise linear approximation of a convex gap
+// cost. Every pair gets its own insertion and deletion matrices.
+
+#[rustfmt::skip]
+use bio_types::alignment::{
+    Alignment           as _Alignment,
+    AlignmentMode       as _AlignmentMode,
+    AlignmentOperation  as _AlignmentOperation,
+};
+use std::mem::swap;
+
+use crate::alignment::scoring::{Scoring, MIN_SCORE};
+
+// Traceback bits: the lowest three bits keep the source of the best score, the others
+// keep whether the gap of every pair is extended or opened in this cell
+const TB_START: u8 = 0;
+const TB_DIAG: u8 = 1;
+const TB_DEL: u8 = 2;
+const TB_DEL_LONG: u8 = 3;
+const TB_INS: u8 = 4;
+const TB_SOURCE: u8 = 0b111;
+const TB_DEL_EXTEND: u8 = 0b1000;
+const TB_INS_EXTEND: u8 = 0b10_0000;
+
+/// Compute the alignment in the global, semiglobal or local mode. Ties of the end are
+/// resolved in favour of the smallest `yend`, then `xend`, like in
+/// `PairwiseAligner::calculate_score`.
+pub fn align(scoring: &Scoring, x: &[u8], y: &[u8], mode: _AlignmentMode) -> _Alignment {
+    let (local, free_y_ends) = match mode {
+        _AlignmentMode::Local => (true, true),
+        _AlignmentMode::Semiglobal => (false, true),
+        _ => (false, false),
+    };
+    let (m, n) = (x.len(), y.len());
+    let long_gap = scoring.long_gap;
+    let del = [
+        (scoring.del_gap_open, scoring.del_gap_extend),
+        long_gap.unwrap_or((scoring.del_gap_open, scoring.del_gap_extend)),
+    ];
+    let ins = [
+        (scoring.ins_gap_open, scoring.ins_gap_extend),
+        long_gap.unwrap_or((scoring.ins_gap_open, scoring.ins_gap_extend)),
+    ];
+
+    let mut traceback = vec![TB_START; (m + 1) * (n + 1)];
+    let cell = |i: usize, j: usize| i * (n + 1) + j;
+
+    // Columns of the best scores and of the scores ending with a deletion of each pair
+    let mut prev_h = vec![MIN_SCORE; m + 1];
+    let mut curr_h = vec![MIN_SCORE; m + 1];
+    let mut prev_d = [vec![MIN_SCORE; m + 1], vec![MIN_SCORE; m + 1]];
+    let mut curr_d = [vec![MIN_SCORE; m + 1], vec![MIN_SCORE; m + 1]];
+    let mut best = (MIN_SCORE, 0, 0);
+
+    for j in 0..=n {
+        let mut ins_scores = [MIN_SCORE; 2];
+        for i in 0..=m {
+            let mut tb = TB_START;
+            let mut h = MIN_SCORE;
+
+            if j > 0 {
+                for k in 0..2 {
+                    let (open, extend) = del[k];
+                    let extended = prev_d[k][i] + extend;
+                    let opened = prev_h[i] + open + extend;
+                    curr_d[k][i] = if extended > opened {
+                        tb |= TB_DEL_EXTEND << k;
+                        extended
+                    } else {
+                        opened
+                    };
+                }
+            }
+            if i > 0 {
+                for k in 0..2 {
+                    let (open, extend) = ins[k];
+                    let extended = ins_scores[k] + extend;
+                    let opened = curr_h[i - 1] + open + extend;
+                    ins_scores[k] = if extended > opened {
+                        tb |= TB_INS_EXTEND << k;
+                        extended
+                    } else {
+                        opened
+                    };
+                }
+            }
+
+            if i > 0 && j > 0 {
+                h = prev_h[i - 1] + (scoring.match_fn)(x[i - 1], y[j - 1]);
+                tb |= TB_DIAG;
+            }
+            if i > 0 {
+                for (k, &score) in ins_scores.iter().enumerate() {
+                    if score > h {
+                        h = score;
+                        tb = (tb & !TB_SOURCE) | (TB_INS + k as u8);
+                    }
+                }
+            }
+            if j > 0 {
+                for (k, d) in curr_d.iter().enumerate() {
+                    if d[i] > h {
+                        h = d[i];
+                        tb = (tb & !TB_SOURCE) | (TB_DEL + k as u8);
+                    }
+                }
+            }
+            let start = (i == 0 && j == 0) || local || (i == 0 && free_y_ends);
+            if start && h <= 0 {
+                h = 0;
+                tb &= !TB_SOURCE;
+            }
+
+            curr_h[i] = h;
+            traceback[cell(i, j)] = tb;
+            if local && h > best.0 {
+                best = (h, i, j);
+            }
+        }
+        if !local && free_y_ends && curr_h[m] > best.0 {
+            best = (curr_h[m], m, j);
+        }
+        swap(&mut prev_h, &mut curr_h);
+        swap(&mut prev_d, &mut curr_d);
+    }
+    if !local && !free_y_ends {
+        best = (prev_h[m], m, n);
+    }
+
+    let (score, xend, yend) = best;
+    let (mut i, mut j) = (xend, yend);
+    let mut source = traceback[cell(i, j)] & TB_SOURCE;
+    let mut operations = Vec::with_capacity(i + j);
+    loop {
+        let tb = traceback[cell(i, j)];
+        match source {
+            TB_START => break,
+            TB_DIAG => {
+                operations.push(if x[i - 1] == y[j - 1] {
+                    _AlignmentOperation::Match
+                } else {
+                    _AlignmentOperation::Subst
+                });
+                i -= 1;
+                j -= 1;
+                source = traceback[cell(i, j)] & TB_SOURCE;
+            }
+            TB_DEL | TB_DEL_LONG => {
+                let k = source - TB_DEL;
+                operations.push(_AlignmentOperation::Del);
+                j -= 1;
+                if tb & (TB_DEL_EXTEND << k) == 0 {
+                    source = traceback[cell(i, j)] & TB_SOURCE;
+                }
+            }
+            _ => {
+                let k = source - TB_INS;
+                operations.push(_AlignmentOperation::Ins);
+                i -= 1;
+                if tb & (TB_INS_EXTEND << k) == 0 {
+                    source = traceback[cell(i, j)] & TB_SOURCE;
+                }
+            }
+        }
+    }
+    operations.reverse();
+
+    _Alignment {
+        score,
+        xstart: i,
+        ystart: j,
+        xend,
+        yend,
+        xlen: m,
+        ylen: n,
+        operations,
+        mode,
+    }
+}
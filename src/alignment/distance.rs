@@ -4,44 +4,341 @@ use bio::alignment::distance::{
     levenshtein         as _levenshtein,
     simd                as _simd,
 };
+#[rustfmt::skip]
+use bio_types::alignment::{
+    Alignment           as _Alignment,
+    AlignmentMode       as _AlignmentMode,
+    AlignmentOperation  as _AlignmentOperation,
+};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyMemoryView};
+use std::sync::Mutex;
+
+use crate::alignment::alignment_type::Alignment;
+use crate::alignment::scoring::resolve_threads;
+use crate::utils::run_parallel;
 
 #[pyfunction]
-fn hamming(alpha: &[u8], beta: &[u8]) -> PyResult<u64> {
+fn hamming(py: Python, alpha: &[u8], beta: &[u8]) -> PyResult<u64> {
     if alpha.len() != beta.len() {
         Err(PyValueError::new_err(
             "hamming distance cannot be calculated for texts of different length",
         ))
     } else {
-        Ok(_hamming(alpha, beta))
+        Ok(py.allow_threads(|| _hamming(alpha, beta)))
     }
 }
 
 #[pyfunction]
-fn simd_hamming(alpha: &[u8], beta: &[u8]) -> PyResult<u64> {
+fn simd_hamming(py: Python, alpha: &[u8], beta: &[u8]) -> PyResult<u64> {
     if alpha.len() != beta.len() {
         Err(PyValueError::new_err(
             "hamming distance cannot be calculated for texts of different length",
         ))
     } else {
-        Ok(_simd::hamming(alpha, beta))
+        Ok(py.allow_threads(|| _simd::hamming(alpha, beta)))
+    }
+}
+
+#[pyfunction]
+fn levenshtein(py: Python, alpha: &[u8], beta: &[u8]) -> u32 {
+    py.allow_threads(|| _levenshtein(alpha, beta))
+}
+
+#[pyfunction]
+fn simd_levenshtein(py: Python, alpha: &[u8], beta: &[u8]) -> u32 {
+    py.allow_threads(|| _simd::levenshtein(alpha, beta))
+}
+
+#[pyfunction]
+fn simd_bounded_levenshtein(py: Python, alpha: &[u8], beta: &[u8], k: u32) -> Option<u32> {
+    py.allow_threads(|| _simd::bounded_levenshtein(alpha, beta, k))
+}
+
+/// Compute the optimal edit script between `alpha` and `beta` with unit costs. Ties are
+/// resolved in favour of matches and substitutions, then insertions.
+fn levenshtein_operations(alpha: &[u8], beta: &[u8]) -> _Alignment {
+    let (m, n) = (alpha.len(), beta.len());
+    let mut distances = vec![vec![0u32; n + 1]; m + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i as u32;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j as u32;
+    }
+    for i in 1..=m {
+        for j in 1..=n {
+            let substitution = distances[i - 1][j - 1] + u32::from(alpha[i - 1] != beta[j - 1]);
+            let gap = distances[i - 1][j].min(distances[i][j - 1]) + 1;
+            distances[i][j] = substitution.min(gap);
+        }
     }
+
+    let (mut i, mut j) = (m, n);
+    let mut operations = Vec::with_capacity(m.max(n));
+    while i > 0 || j > 0 {
+        let distance = distances[i][j];
+        if i > 0
+            && j > 0
+            && distances[i - 1][j - 1] + u32::from(alpha[i - 1] != beta[j - 1]) == distance
+        {
+            operations.push(if alpha[i - 1] == beta[j - 1] {
+                _AlignmentOperation::Match
+            } else {
+                _AlignmentOperation::Subst
+            });
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && distances[i - 1][j] + 1 == distance {
+            operations.push(_AlignmentOperation::Ins);
+            i -= 1;
+        } else {
+            operations.push(_AlignmentOperation::Del);
+            j -= 1;
+        }
+    }
+    operations.reverse();
+
+    _Alignment {
+        score: -(distances[m][n] as i32),
+        xstart: 0,
+        ystart: 0,
+        xend: m,
+        yend: n,
+        xlen: m,
+        ylen: n,
+        operations,
+        mode: _AlignmentMode::Global,
+    }
+}
+
+/// Return the optimal edit operations between `alpha` and `beta` as a global alignment,
+/// where `alpha` is x and `beta` is y. Every edit costs 1, so the score is the negated
+/// Levenshtein distance.
+#[pyfunction]
+fn levenshtein_alignment(py: Python, alpha: &[u8], beta: &[u8]) -> Alignment {
+    Alignment(py.allow_threads(|| levenshtein_operations(alpha, beta)))
 }
 
+/// Compute the Damerau-Levenshtein distance, where a transposition of two adjacent symbols
+/// costs 1 like other edits. Transposed symbols can be edited further, unlike in the
+/// optimal string alignment distance.
 #[pyfunction]
-fn levenshtein(alpha: &[u8], beta: &[u8]) -> u32 {
-    _levenshtein(alpha, beta)
+fn damerau_levenshtein(py: Python, alpha: &[u8], beta: &[u8]) -> u32 {
+    py.allow_threads(|| {
+        let (m, n) = (alpha.len(), beta.len());
+        let infinity = (m + n) as u32;
+        // Distances are shifted by one row and column to keep the infinite border
+        let mut distances = vec![vec![0u32; n + 2]; m + 2];
+        distances[0][0] = infinity;
+        for i in 0..=m {
+            distances[i + 1][0] = infinity;
+            distances[i + 1][1] = i as u32;
+        }
+        for j in 0..=n {
+            distances[0][j + 1] = infinity;
+            distances[1][j + 1] = j as u32;
+        }
+
+        // Last row where every symbol was seen in alpha
+        let mut last_rows = [0usize; 256];
+        for i in 1..=m {
+            // Last column of the current row where beta matched alpha
+            let mut last_column = 0;
+            for j in 1..=n {
+                let (last_row, prev_column) = (last_rows[beta[j - 1] as usize], last_column);
+                let cost = if alpha[i - 1] == beta[j - 1] {
+                    last_column = j;
+                    0
+                } else {
+                    1
+                };
+                distances[i + 1][j + 1] = (distances[i][j] + cost)
+                    .min(distances[i + 1][j] + 1)
+                    .min(distances[i][j + 1] + 1)
+                    .min(
+                        distances[last_row][prev_column]
+                            + (i - last_row - 1) as u32
+                            + 1
+                            + (j - prev_column - 1) as u32,
+                    );
+            }
+            last_rows[alpha[i - 1] as usize] = i;
+        }
+        distances[m + 1][n + 1]
+    })
+}
+
+#[derive(Clone, Copy)]
+pub enum Metric {
+    Hamming,
+    Levenshtein,
+}
+
+impl Metric {
+    pub fn parse(metric: &str) -> PyResult<Self> {
+        match metric {
+            "hamming" => Ok(Metric::Hamming),
+            "levenshtein" => Ok(Metric::Levenshtein),
+            _ => Err(PyValueError::new_err(
+                "metric must be 'hamming' or 'levenshtein'",
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Hamming => "hamming",
+            Metric::Levenshtein => "levenshtein",
+        }
+    }
+
+    /// Check that the distance can be calculated between the query and every sequence.
+    pub fn check(self, query: &[u8], sequences: &[Vec<u8>]) -> PyResult<()> {
+        match self {
+            Metric::Hamming if sequences.iter().any(|s| s.len() != query.len()) => {
+                Err(PyValueError::new_err(
+                    "hamming distance cannot be calculated for texts of different length",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Compute the distance, or `max_dist + 1` when it's greater than `max_dist`.
+    pub fn distance(self, alpha: &[u8], beta: &[u8], max_dist: Option<u32>) -> u32 {
+        let distance = match (self, max_dist) {
+            (Metric::Hamming, _) => _simd::hamming(alpha, beta).min(u32::MAX as u64) as u32,
+            (Metric::Levenshtein, None) => _simd::levenshtein(alpha, beta),
+            (Metric::Levenshtein, Some(k)) => {
+                _simd::bounded_levenshtein(alpha, beta, k).unwrap_or(u32::MAX)
+            }
+        };
+        match max_dist {
+            Some(k) if distance > k => k.saturating_add(1),
+            _ => distance,
+        }
+    }
+}
+
+/// Write the values into the buffer as native-endian unsigned 32-bit integers.
+fn write_u32s(buffer: &mut [u8], values: impl IntoIterator<Item = u32>) {
+    for (cell, value) in buffer.chunks_exact_mut(4).zip(values) {
+        cell.copy_from_slice(&value.to_ne_bytes());
+    }
 }
 
+/// Create a memoryview of `len` unsigned 32-bit integers, which `fill` writes directly
+/// into the python buffer without holding the GIL. It can be used with `numpy.asarray`
+/// without copying the values into python lists.
+fn new_u32_memoryview<'p, F>(py: Python<'p>, len: usize, fill: F) -> PyResult<&'p PyAny>
+where
+    F: FnOnce(&mut [u8]) + Send,
+{
+    let bytes = PyBytes::new_with(py, len * 4, |buffer| {
+        py.allow_threads(|| fill(buffer));
+        Ok(())
+    })?;
+    PyMemoryView::from(bytes)?.call_method1("cast", ("I",))
+}
+
+/// Compute the distances between all pairs of the sequences. Without `max_dist`, returns
+/// a memoryview of the condensed upper triangle of the distance matrix, in the order of
+/// `scipy.spatial.distance.pdist`. With `max_dist`, returns memoryviews `(i, j, d)` of
+/// the pairs `i < j` within `max_dist`, so the matrix isn't materialized for many
+/// sequences and can be built with `scipy.sparse.coo_matrix((d, (i, j)))`.
 #[pyfunction]
-fn simd_levenshtein(alpha: &[u8], beta: &[u8]) -> u32 {
-    _simd::levenshtein(alpha, beta)
+#[pyo3(signature = (seqs, metric="levenshtein", max_dist=None, threads=None))]
+fn pairwise_matrix(
+    py: Python,
+    seqs: Vec<Vec<u8>>,
+    metric: &str,
+    max_dist: Option<u32>,
+    threads: Option<usize>,
+) -> PyResult<PyObject> {
+    let metric = Metric::parse(metric)?;
+    let Some(first) = seqs.first() else {
+        return Err(PyValueError::new_err("seqs can't be empty"));
+    };
+    metric.check(first, &seqs)?;
+    let threads = resolve_threads(threads)?;
+    let count = seqs.len();
+
+    let Some(max_dist) = max_dist else {
+        let view = new_u32_memoryview(py, count * (count - 1) / 2, |buffer| {
+            // Every task fills its own row of the triangle
+            let mut rest = buffer;
+            let rows: Vec<Mutex<&mut [u8]>> = (0..count)
+                .map(|i| {
+                    let (row, tail) = std::mem::take(&mut rest).split_at_mut((count - 1 - i) * 4);
+                    rest = tail;
+                    Mutex::new(row)
+                })
+                .collect();
+            run_parallel(count, threads, |i| {
+                let distances = seqs[i + 1..]
+                    .iter()
+                    .map(|other| metric.distance(&seqs[i], other, None));
+                write_u32s(&mut rows[i].lock().unwrap(), distances);
+            });
+        })?;
+        return Ok(view.into());
+    };
+
+    let close = py.allow_threads(|| {
+        run_parallel(count, threads, |i| {
+            (i + 1..count)
+                .filter_map(|j| {
+                    let distance = metric.distance(&seqs[i], &seqs[j], Some(max_dist));
+                    (distance <= max_dist).then_some((j as u32, distance))
+                })
+                .collect::<Vec<(u32, u32)>>()
+        })
+    });
+    let total = close.iter().map(Vec::len).sum();
+    let pairs = || {
+        close
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| row.iter().map(move |&(j, d)| (i as u32, j, d)))
+    };
+    let i = new_u32_memoryview(py, total, |buffer| {
+        write_u32s(buffer, pairs().map(|(i, _, _)| i))
+    })?;
+    let j = new_u32_memoryview(py, total, |buffer| {
+        write_u32s(buffer, pairs().map(|(_, j, _)| j))
+    })?;
+    let d = new_u32_memoryview(py, total, |buffer| {
+        write_u32s(buffer, pairs().map(|(_, _, d)| d))
+    })?;
+    Ok((i, j, d).into_py(py))
 }
 
+/// Compute the distances from the query to every sequence. Distances greater than
+/// `max_dist` are reported as `max_dist + 1`. Returns a memoryview of length `n`.
 #[pyfunction]
-fn simd_bounded_levenshtein(alpha: &[u8], beta: &[u8], k: u32) -> Option<u32> {
-    _simd::bounded_levenshtein(alpha, beta, k)
+#[pyo3(signature = (query, seqs, metric="levenshtein", max_dist=None, threads=None))]
+fn one_to_many<'p>(
+    py: Python<'p>,
+    query: &[u8],
+    seqs: Vec<Vec<u8>>,
+    metric: &str,
+    max_dist: Option<u32>,
+    threads: Option<usize>,
+) -> PyResult<&'p PyAny> {
+    let metric = Metric::parse(metric)?;
+    if seqs.is_empty() {
+        return Err(PyValueError::new_err("seqs can't be empty"));
+    }
+    metric.check(query, &seqs)?;
+    let threads = resolve_threads(threads)?;
+    new_u32_memoryview(py, seqs.len(), |buffer| {
+        let distances = run_parallel(seqs.len(), threads, |i| {
+            metric.distance(query, &seqs[i], max_dist)
+        });
+        write_u32s(buffer, distances);
+    })
 }
 
 #[pymodule]
@@ -51,5 +348,9 @@ pub fn distance(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(levenshtein, m)?)?;
     m.add_function(wrap_pyfunction!(simd_levenshtein, m)?)?;
     m.add_function(wrap_pyfunction!(simd_bounded_levenshtein, m)?)?;
+    m.add_function(wrap_pyfunction!(levenshtein_alignment, m)?)?;
+    m.add_function(wrap_pyfunction!(damerau_levenshtein, m)?)?;
+    m.add_function(wrap_pyfunction!(pairwise_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(one_to_many, m)?)?;
     Ok(())
 }
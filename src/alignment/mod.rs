@@ -1,10 +1,18 @@
+use bio_types::alignment::AlignmentOperation as _AlignmentOperation;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use pyo3::wrap_pymodule;
 
 mod alignment_type;
-mod distance;
-mod scoring;
+mod banded;
+mod convex;
+pub(crate) mod distance;
+mod msa;
+mod poa;
+pub(crate) mod scoring;
+mod sparse;
+mod striped;
+mod xdrop;
 
 #[pymodule]
 pub fn alignment(py: Python, m: &PyModule) -> PyResult<()> {
@@ -16,17 +24,37 @@ pub fn alignment(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<alignment_type::Xclip>()?;
     m.add_class::<alignment_type::Yclip>()?;
     m.add_class::<alignment_type::Alignment>()?;
+    for (name, operation) in [
+        ("MATCH", _AlignmentOperation::Match),
+        ("SUBST", _AlignmentOperation::Subst),
+        ("DEL", _AlignmentOperation::Del),
+        ("INS", _AlignmentOperation::Ins),
+    ] {
+        m.add(
+            name,
+            alignment_type::rust_bio_alignment_operation_into_py_object(operation, py),
+        )?;
+    }
+    m.add_function(wrap_pyfunction!(alignment_type::parse_cigar, m)?)?;
     m.add_class::<scoring::Scoring>()?;
+    m.add("MIN_SCORE", scoring::MIN_SCORE)?;
     m.add(
         "DEFAULT_ALIGNER_CAPACITY",
         scoring::DEFAULT_ALIGNER_CAPACITY,
     )?;
     m.add_class::<scoring::PairwiseAligner>()?;
+    m.add_class::<banded::BandedAligner>()?;
 
     m.add_wrapped(wrap_pymodule!(distance::distance))?;
+    m.add_wrapped(wrap_pymodule!(msa::msa))?;
+    m.add_wrapped(wrap_pymodule!(poa::poa))?;
+    m.add_wrapped(wrap_pymodule!(sparse::sparse))?;
     let sys = PyModule::import(py, "sys")?;
     let sys_modules: &PyDict = sys.getattr("modules")?.downcast()?;
     sys_modules.set_item("bioforma.alignment.distance", m.getattr("distance")?)?;
+    sys_modules.set_item("bioforma.alignment.msa", m.getattr("msa")?)?;
+    sys_modules.set_item("bioforma.alignment.poa", m.getattr("poa")?)?;
+    sys_modules.set_item("bioforma.alignment.sparse", m.getattr("sparse")?)?;
 
     Ok(())
 }
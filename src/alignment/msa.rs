@@ -0,0 +1,755 @@
+// Progressive multiple sequence alignment: sequences are aligned pairwise to build
+// a UPGMA guide tree from their distances, then the profiles are aligned to each other
+// following the tree, from the closest sequences to the most distant ones.
+
+#[rustfmt::skip]
+use bio_types::alignment::{
+    AlignmentMode       as _AlignmentMode,
+    AlignmentOperation  as _AlignmentOperation,
+};
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyType};
+
+use crate::alignment::scoring::{resolve_threads, PairwiseAligner, Scoring};
+use crate::alphabets::Alphabet;
+use crate::utils::richcmp;
+
+const GAP: u8 = b'-';
+
+/// Multiple sequence alignment of gapped sequences of the same length, where gaps
+/// are written as `-`.
+#[derive(Clone)]
+#[pyclass(module = "bioforma.alignment.msa")]
+pub struct MultipleAlignment {
+    names: Vec<String>,
+    rows: Vec<Vec<u8>>,
+}
+
+impl MultipleAlignment {
+    pub fn from_rows(rows: Vec<Vec<u8>>, names: Option<Vec<String>>) -> PyResult<Self> {
+        if rows.is_empty() {
+            return Err(PyValueError::new_err("sequences can't be empty"));
+        }
+        if rows.iter().any(|row| row.len() != rows[0].len()) {
+            return Err(PyValueError::new_err(
+                "All sequences must have the same length",
+            ));
+        }
+        let names = match names {
+            Some(names) if names.len() != rows.len() => {
+                return Err(PyValueError::new_err(
+                    "names must have the same length as sequences",
+                ))
+            }
+            Some(names) => names,
+            None => default_names(rows.len()),
+        };
+        Ok(MultipleAlignment { names, rows })
+    }
+
+    fn num_columns(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    fn column_symbols(&self, index: usize) -> impl Iterator<Item = u8> + '_ {
+        self.rows.iter().map(move |row| row[index])
+    }
+
+    /// Counts of the symbols of the column in the order of their first occurrence, gaps
+    /// excluded, and the number of gaps.
+    fn column_counts(&self, index: usize) -> (Vec<(u8, usize)>, usize) {
+        let mut counts: Vec<(u8, usize)> = Vec::new();
+        let mut gaps = 0;
+        for symbol in self.column_symbols(index) {
+            if symbol == GAP {
+                gaps += 1;
+            } else if let Some(count) = counts.iter_mut().find(|(s, _)| *s == symbol) {
+                count.1 += 1;
+            } else {
+                counts.push((symbol, 1));
+            }
+        }
+        (counts, gaps)
+    }
+
+    /// Build the consensus from the symbols of every column, where columns with more gaps
+    /// than symbols are gaps.
+    fn build_consensus<F>(&self, include_gaps: bool, mut symbol: F) -> PyResult<Vec<u8>>
+    where
+        F: FnMut(&[(u8, usize)]) -> PyResult<u8>,
+    {
+        let mut consensus = Vec::with_capacity(self.num_columns());
+        for index in 0..self.num_columns() {
+            let (counts, gaps) = self.column_counts(index);
+            if gaps > self.rows.len() - gaps {
+                if include_gaps {
+                    consensus.push(GAP);
+                }
+            } else {
+                consensus.push(symbol(&counts)?);
+            }
+        }
+        Ok(consensus)
+    }
+
+    /// Rows of the alignment with width of `line_width` columns.
+    fn blocks(&self, line_width: usize) -> PyResult<Vec<(usize, usize)>> {
+        if line_width == 0 {
+            return Err(PyValueError::new_err("line_width must be positive"));
+        }
+        let length = self.num_columns();
+        Ok((0..length.max(1))
+            .step_by(line_width)
+            .map(|start| (start, (start + line_width).min(length)))
+            .collect())
+    }
+}
+
+/// Bit mask of the nucleotides, A, C, G and T, of the IUPAC code.
+fn iupac_mask(symbol: u8) -> Option<u8> {
+    let mask = match symbol.to_ascii_uppercase() {
+        b'A' => 0b0001,
+        b'C' => 0b0010,
+        b'G' => 0b0100,
+        b'T' | b'U' => 0b1000,
+        b'R' => 0b0101,
+        b'Y' => 0b1010,
+        b'S' => 0b0110,
+        b'W' => 0b1001,
+        b'K' => 0b1100,
+        b'M' => 0b0011,
+        b'B' => 0b1110,
+        b'D' => 0b1101,
+        b'H' => 0b1011,
+        b'V' => 0b0111,
+        b'N' => 0b1111,
+        _ => return None,
+    };
+    Some(mask)
+}
+
+fn default_names(count: usize) -> Vec<String> {
+    (1..=count).map(|i| format!("seq{i}")).collect()
+}
+
+/// Clustal conservation symbol of the column: `*` for identical residues, `:` and `.`
+/// for residues from one of the strong or weak groups.
+fn clustal_conservation(column: &[u8]) -> char {
+    const STRONG: [&[u8]; 9] = [
+        b"STA", b"NEQK", b"NHQK", b"NDEQ", b"QHRK", b"MILV", b"MILF", b"HY", b"FYW",
+    ];
+    const WEAK: [&[u8]; 11] = [
+        b"CSA", b"ATV", b"SAG", b"STNK", b"STPA", b"SGND", b"SNDEQK", b"NDEQHK", b"NEQHRK",
+        b"FVLIM", b"HFY",
+    ];
+    if column.contains(&GAP) {
+        return ' ';
+    }
+    let column: Vec<u8> = column.iter().map(u8::to_ascii_uppercase).collect();
+    let in_group = |group: &&[u8]| column.iter().all(|symbol| group.contains(symbol));
+    if column.iter().all(|&symbol| symbol == column[0]) {
+        '*'
+    } else if STRONG.iter().any(in_group) {
+        ':'
+    } else if WEAK.iter().any(in_group) {
+        '.'
+    } else {
+        ' '
+    }
+}
+
+#[pymethods]
+impl MultipleAlignment {
+    #[new]
+    #[pyo3(signature = (sequences, names=None, alphabet=None))]
+    pub fn new(
+        sequences: Vec<Vec<u8>>,
+        names: Option<Vec<String>>,
+        alphabet: Option<&Alphabet>,
+    ) -> PyResult<Self> {
+        let alignment = Self::from_rows(sequences, names)?;
+        if let Some(alphabet) = alphabet {
+            alignment.validate(alphabet)?;
+        }
+        Ok(alignment)
+    }
+
+    /// Check that all symbols of the sequences, except gaps, are in the alphabet.
+    pub fn validate(&self, alphabet: &Alphabet) -> PyResult<()> {
+        for (name, row) in self.names.iter().zip(self.rows.iter()) {
+            if !alphabet
+                .0
+                .is_word(row.iter().filter(|&&symbol| symbol != GAP))
+            {
+                return Err(PyValueError::new_err(format!(
+                    "Sequence {name} has symbols out of the alphabet"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the fraction of sequences with the most frequent symbol of every column.
+    pub fn conservation(&self) -> Vec<f64> {
+        (0..self.num_columns())
+            .map(|index| {
+                let (counts, _) = self.column_counts(index);
+                let best = counts.iter().map(|&(_, count)| count).max().unwrap_or(0);
+                best as f64 / self.rows.len() as f64
+            })
+            .collect()
+    }
+
+    /// Return the Shannon entropy in bits of the symbols of every column, gaps excluded.
+    pub fn entropy(&self) -> Vec<f64> {
+        (0..self.num_columns())
+            .map(|index| {
+                let (counts, gaps) = self.column_counts(index);
+                let total = (self.rows.len() - gaps) as f64;
+                counts.iter().fold(0.0, |entropy, &(_, count)| {
+                    let p = count as f64 / total;
+                    entropy - p * p.log2()
+                })
+            })
+            .collect()
+    }
+
+    /// Return the fraction of gaps of every column.
+    pub fn gap_fraction(&self) -> Vec<f64> {
+        (0..self.num_columns())
+            .map(|index| self.column_counts(index).1 as f64 / self.rows.len() as f64)
+            .collect()
+    }
+
+    /// Return the majority consensus: the most frequent symbol of every column, or the
+    /// first one of them in case of a tie. Columns with more gaps than symbols are skipped,
+    /// or kept as gaps with `include_gaps`.
+    #[pyo3(signature = (include_gaps=false))]
+    pub fn consensus<'p>(&self, py: Python<'p>, include_gaps: bool) -> PyResult<&'p PyBytes> {
+        let consensus = self.build_consensus(include_gaps, |counts| {
+            let mut best = counts[0];
+            for &(symbol, count) in counts.iter().skip(1) {
+                if count > best.1 {
+                    best = (symbol, count);
+                }
+            }
+            Ok(best.0)
+        })?;
+        Ok(PyBytes::new(py, &consensus))
+    }
+
+    /// Return the IUPAC consensus of nucleotide sequences: the code covering all
+    /// nucleotides of every column. Gaps are treated like in the majority consensus.
+    #[pyo3(signature = (include_gaps=false))]
+    pub fn iupac_consensus<'p>(&self, py: Python<'p>, include_gaps: bool) -> PyResult<&'p PyBytes> {
+        const CODES: &[u8; 16] = b"-ACMGRSVTWYHKDBN";
+        let consensus = self.build_consensus(include_gaps, |counts| {
+            let mut mask = 0;
+            for &(symbol, _) in counts.iter() {
+                mask |= iupac_mask(symbol).ok_or_else(|| {
+                    PyValueError::new_err(format!(
+                        "Symbol {} isn't a nucleotide IUPAC code",
+                        symbol as char
+                    ))
+                })?;
+            }
+            Ok(CODES[mask as usize])
+        })?;
+        Ok(PyBytes::new(py, &consensus))
+    }
+
+    /// Return the percent identity of every pair of sequences: the percentage of identical
+    /// symbols among the columns where both sequences have symbols.
+    pub fn identity_matrix(&self) -> Vec<Vec<f64>> {
+        let count = self.rows.len();
+        let mut matrix = vec![vec![100.0; count]; count];
+        for (i, x) in self.rows.iter().enumerate() {
+            for (j, y) in self.rows.iter().enumerate().skip(i + 1) {
+                let (mut identical, mut aligned) = (0, 0);
+                for (&a, &b) in x.iter().zip(y.iter()) {
+                    if a != GAP && b != GAP {
+                        aligned += 1;
+                        if a == b {
+                            identical += 1;
+                        }
+                    }
+                }
+                let identity = if aligned == 0 {
+                    0.0
+                } else {
+                    100.0 * identical as f64 / aligned as f64
+                };
+                matrix[i][j] = identity;
+                matrix[j][i] = identity;
+            }
+        }
+        matrix
+    }
+
+    #[getter]
+    pub fn sequences<'p>(&self, py: Python<'p>) -> Vec<&'p PyBytes> {
+        self.rows.iter().map(|row| PyBytes::new(py, row)).collect()
+    }
+
+    #[getter]
+    pub fn names(&self) -> Vec<String> {
+        self.names.clone()
+    }
+
+    #[getter(num_columns)]
+    pub fn get_num_columns(&self) -> usize {
+        self.num_columns()
+    }
+
+    /// Return the symbols of the column `index`, one per sequence.
+    pub fn column<'p>(&self, py: Python<'p>, index: usize) -> PyResult<&'p PyBytes> {
+        if index >= self.num_columns() {
+            return Err(PyIndexError::new_err("column index out of range"));
+        }
+        let column: Vec<u8> = self.column_symbols(index).collect();
+        Ok(PyBytes::new(py, &column))
+    }
+
+    /// Return the sequence `index` without gaps.
+    pub fn ungapped<'p>(&self, py: Python<'p>, index: usize) -> PyResult<&'p PyBytes> {
+        let row = self
+            .rows
+            .get(index)
+            .ok_or_else(|| PyIndexError::new_err("sequence index out of range"))?;
+        let sequence: Vec<u8> = row
+            .iter()
+            .copied()
+            .filter(|&symbol| symbol != GAP)
+            .collect();
+        Ok(PyBytes::new(py, &sequence))
+    }
+
+    #[pyo3(signature = (line_width=60))]
+    pub fn to_fasta(&self, line_width: usize) -> PyResult<String> {
+        let blocks = self.blocks(line_width)?;
+        let mut fasta = String::new();
+        for (name, row) in self.names.iter().zip(self.rows.iter()) {
+            fasta.push_str(&format!(">{name}\n"));
+            for &(start, end) in blocks.iter() {
+                fasta.push_str(&String::from_utf8_lossy(&row[start..end]));
+                fasta.push('\n');
+            }
+        }
+        Ok(fasta)
+    }
+
+    #[pyo3(signature = (line_width=60))]
+    pub fn to_clustal(&self, line_width: usize) -> PyResult<String> {
+        let blocks = self.blocks(line_width)?;
+        let name_width = self.names.iter().map(String::len).max().unwrap_or(0) + 4;
+        let mut clustal = String::from("CLUSTAL W multiple sequence alignment\n");
+        for &(start, end) in blocks.iter() {
+            clustal.push('\n');
+            for (name, row) in self.names.iter().zip(self.rows.iter()) {
+                clustal.push_str(&format!(
+                    "{name:<name_width$}{}\n",
+                    String::from_utf8_lossy(&row[start..end])
+                ));
+            }
+            let conservation: String = (start..end)
+                .map(|i| {
+                    let column: Vec<u8> = self.rows.iter().map(|row| row[i]).collect();
+                    clustal_conservation(&column)
+                })
+                .collect();
+            let conservation = format!("{:name_width$}{conservation}", "");
+            clustal.push_str(conservation.trim_end());
+            clustal.push('\n');
+        }
+        Ok(clustal)
+    }
+
+    fn __reduce__<'p>(
+        slf: &'p PyCell<Self>,
+        py: Python<'p>,
+    ) -> (&'p PyType, (Vec<&'p PyBytes>, Vec<String>)) {
+        let alignment = slf.borrow();
+        (
+            slf.get_type(),
+            (alignment.sequences(py), alignment.names.clone()),
+        )
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
+        richcmp((&self.names, &self.rows), (&other.names, &other.rows), op)
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn __getitem__<'p>(&self, py: Python<'p>, index: usize) -> PyResult<&'p PyBytes> {
+        self.rows
+            .get(index)
+            .map(|row| PyBytes::new(py, row))
+            .ok_or_else(|| PyIndexError::new_err("sequence index out of range"))
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "<MultipleAlignment: sequences={}, columns={}>",
+            self.rows.len(),
+            self.num_columns()
+        )
+    }
+}
+
+/// Node of the guide tree: a leaf with the index of the sequence or a merge of two
+/// subtrees at the given height.
+enum GuideTree {
+    Leaf(usize),
+    Node(Box<GuideTree>, Box<GuideTree>, f64),
+}
+
+impl GuideTree {
+    fn height(&self) -> f64 {
+        match self {
+            GuideTree::Leaf(_) => 0.0,
+            GuideTree::Node(_, _, height) => *height,
+        }
+    }
+
+    fn to_newick(&self, names: &[String], parent_height: f64) -> String {
+        let branch = parent_height - self.height();
+        match self {
+            GuideTree::Leaf(index) => format!("{}:{branch:.5}", names[*index]),
+            GuideTree::Node(left, right, height) => format!(
+                "({},{}):{branch:.5}",
+                left.to_newick(names, *height),
+                right.to_newick(names, *height)
+            ),
+        }
+    }
+}
+
+/// Compute the distances between all pairs of sequences as one minus the identity of
+/// their global alignment, where the identity ignores gaps.
+fn distance_matrix(
+    scoring: &Scoring,
+    sequences: &[Vec<u8>],
+    threads: usize,
+) -> PyResult<Vec<Vec<f64>>> {
+    let count = sequences.len();
+    let indices: Vec<(usize, usize)> = (0..count)
+        .flat_map(|i| (i + 1..count).map(move |j| (i, j)))
+        .collect();
+    let pairs: Vec<(&[u8], &[u8])> = indices
+        .iter()
+        .map(|&(i, j)| (sequences[i].as_slice(), sequences[j].as_slice()))
+        .collect();
+    let mut aligner = PairwiseAligner::new(scoring.clone(), 0, 0);
+    let alignments = aligner.align_pairs(&pairs, _AlignmentMode::Global, threads)?;
+
+    let mut distances = vec![vec![0.0; count]; count];
+    for ((i, j), alignment) in indices.into_iter().zip(alignments) {
+        let (mut matches, mut aligned) = (0, 0);
+        for operation in alignment.operations {
+            match operation {
+                _AlignmentOperation::Match => {
+                    matches += 1;
+                    aligned += 1;
+                }
+                _AlignmentOperation::Subst => aligned += 1,
+                _ => {}
+            }
+        }
+        let identity = if aligned == 0 {
+            0.0
+        } else {
+            matches as f64 / aligned as f64
+        };
+        distances[i][j] = 1.0 - identity;
+        distances[j][i] = 1.0 - identity;
+    }
+    Ok(distances)
+}
+
+/// Build the UPGMA tree: the closest clusters are merged first, and the distance to the
+/// merged cluster is the average of the distances to all of its sequences.
+fn upgma(mut distances: Vec<Vec<f64>>) -> GuideTree {
+    let mut clusters: Vec<Option<(GuideTree, usize)>> = (0..distances.len())
+        .map(|i| Some((GuideTree::Leaf(i), 1)))
+        .collect();
+    loop {
+        let active: Vec<usize> = (0..clusters.len())
+            .filter(|&i| clusters[i].is_some())
+            .collect();
+        if active.len() == 1 {
+            return clusters[active[0]].take().expect("active cluster").0;
+        }
+
+        let mut closest = (f64::INFINITY, 0, 0);
+        for (k, &i) in active.iter().enumerate() {
+            for &j in active[k + 1..].iter() {
+                if distances[i][j] < closest.0 {
+                    closest = (distances[i][j], i, j);
+                }
+            }
+        }
+        let (distance, i, j) = closest;
+        let (left, left_size) = clusters[i].take().expect("active cluster");
+        let (right, right_size) = clusters[j].take().expect("active cluster");
+        let size = left_size + right_size;
+        for &k in active.iter().filter(|&&k| k != i && k != j) {
+            let merged = (distances[i][k] * left_size as f64 + distances[j][k] * right_size as f64)
+                / size as f64;
+            distances[i][k] = merged;
+            distances[k][i] = merged;
+        }
+        let node = GuideTree::Node(Box::new(left), Box::new(right), distance / 2.0);
+        clusters[i] = Some((node, size));
+    }
+}
+
+/// Aligned sequences of a subtree as the indices of the sequences and their gapped rows.
+struct Profile {
+    indices: Vec<usize>,
+    rows: Vec<Vec<u8>>,
+}
+
+impl Profile {
+    fn len(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    /// Rows spread over the columns of a new alignment, where `None` is a gap column.
+    fn gapped(&self, columns: &[Option<usize>]) -> Vec<Vec<u8>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                columns
+                    .iter()
+                    .map(|column| column.map_or(GAP, |k| row[k]))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Counts of the symbols of every column, gaps excluded.
+    fn column_counts(&self) -> Vec<Vec<(u8, u32)>> {
+        (0..self.len())
+            .map(|i| {
+                let mut counts = [0u32; 256];
+                for row in self.rows.iter() {
+                    counts[row[i] as usize] += 1;
+                }
+                counts[GAP as usize] = 0;
+                (0..=255u8)
+                    .filter(|&symbol| counts[symbol as usize] > 0)
+                    .map(|symbol| (symbol, counts[symbol as usize]))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// Traceback bits of the profile alignment, following the X-drop extension
+const TB_DIAG: u8 = 0;
+const TB_DEL: u8 = 1;
+const TB_INS: u8 = 2;
+const TB_SOURCE: u8 = 0b11;
+const TB_DEL_EXTEND: u8 = 0b100;
+const TB_INS_EXTEND: u8 = 0b1000;
+
+/// Align two profiles globally with affine gaps. The score of two columns is the average
+/// score of all pairs of their symbols, where pairs with gaps score zero.
+fn align_profiles(scoring: &Scoring, x: &Profile, y: &Profile) -> Profile {
+    let (m, n) = (x.len(), y.len());
+    let (x_counts, y_counts) = (x.column_counts(), y.column_counts());
+    let pairs = (x.rows.len() * y.rows.len()) as f64;
+    let column_score = |i: usize, j: usize| {
+        let mut score = 0.0;
+        for &(a, a_count) in x_counts[i].iter() {
+            for &(b, b_count) in y_counts[j].iter() {
+                score += (scoring.match_fn)(a, b) as f64 * (a_count * b_count) as f64;
+            }
+        }
+        score / pairs
+    };
+    let ins_open = (scoring.ins_gap_open + scoring.ins_gap_extend) as f64;
+    let ins_extend = scoring.ins_gap_extend as f64;
+    let del_open = (scoring.del_gap_open + scoring.del_gap_extend) as f64;
+    let del_extend = scoring.del_gap_extend as f64;
+
+    // Rows of the best scores and of the scores ending with an insertion, i.e. the column
+    // of x against gaps
+    let mut traceback = vec![TB_DIAG; (m + 1) * (n + 1)];
+    let cell = |i: usize, j: usize| i * (n + 1) + j;
+    let mut prev_h: Vec<f64> = (0..=n)
+        .map(|j| del_open + del_extend * (j as f64 - 1.0))
+        .collect();
+    prev_h[0] = 0.0;
+    for j in 1..=n {
+        traceback[cell(0, j)] = TB_DEL | if j > 1 { TB_DEL_EXTEND } else { 0 };
+    }
+    let mut prev_f = vec![f64::NEG_INFINITY; n + 1];
+    let mut curr_h = vec![0.0; n + 1];
+    let mut curr_f = vec![f64::NEG_INFINITY; n + 1];
+
+    for i in 1..=m {
+        curr_h[0] = ins_open + ins_extend * (i as f64 - 1.0);
+        curr_f[0] = curr_h[0];
+        traceback[cell(i, 0)] = TB_INS | if i > 1 { TB_INS_EXTEND } else { 0 };
+        let mut e = f64::NEG_INFINITY;
+        for j in 1..=n {
+            let mut tb = TB_DIAG;
+            if e + del_extend > curr_h[j - 1] + del_open {
+                e += del_extend;
+                tb |= TB_DEL_EXTEND;
+            } else {
+                e = curr_h[j - 1] + del_open;
+            }
+            if prev_f[j] + ins_extend > prev_h[j] + ins_open {
+                curr_f[j] = prev_f[j] + ins_extend;
+                tb |= TB_INS_EXTEND;
+            } else {
+                curr_f[j] = prev_h[j] + ins_open;
+            }
+
+            let mut h = prev_h[j - 1] + column_score(i - 1, j - 1);
+            if curr_f[j] > h {
+                h = curr_f[j];
+                tb |= TB_INS;
+            }
+            if e > h {
+                h = e;
+                tb = (tb & !TB_SOURCE) | TB_DEL;
+            }
+            curr_h[j] = h;
+            traceback[cell(i, j)] = tb;
+        }
+        std::mem::swap(&mut prev_h, &mut curr_h);
+        std::mem::swap(&mut prev_f, &mut curr_f);
+    }
+
+    let (mut i, mut j) = (m, n);
+    let mut source = TB_DIAG;
+    let mut columns: Vec<(Option<usize>, Option<usize>)> = Vec::with_capacity(m + n);
+    while i > 0 || j > 0 {
+        let tb = traceback[cell(i, j)];
+        if source == TB_DIAG {
+            source = tb & TB_SOURCE;
+        }
+        match source {
+            TB_DEL => {
+                columns.push((None, Some(j - 1)));
+                if tb & TB_DEL_EXTEND == 0 {
+                    source = TB_DIAG;
+                }
+                j -= 1;
+            }
+            TB_INS => {
+                columns.push((Some(i - 1), None));
+                if tb & TB_INS_EXTEND == 0 {
+                    source = TB_DIAG;
+                }
+                i -= 1;
+            }
+            _ => {
+                columns.push((Some(i - 1), Some(j - 1)));
+                i -= 1;
+                j -= 1;
+            }
+        }
+    }
+    columns.reverse();
+
+    let (x_columns, y_columns): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+    let mut rows = x.gapped(&x_columns);
+    rows.extend(y.gapped(&y_columns));
+    let mut indices = x.indices.clone();
+    indices.extend(y.indices.iter().copied());
+    Profile { indices, rows }
+}
+
+fn align_tree(scoring: &Scoring, tree: &GuideTree, sequences: &[Vec<u8>]) -> Profile {
+    match tree {
+        GuideTree::Leaf(index) => Profile {
+            indices: vec![*index],
+            rows: vec![sequences[*index].clone()],
+        },
+        GuideTree::Node(left, right, _) => {
+            let left = align_tree(scoring, left, sequences);
+            let right = align_tree(scoring, right, sequences);
+            align_profiles(scoring, &left, &right)
+        }
+    }
+}
+
+fn check_sequences(sequences: &[Vec<u8>]) -> PyResult<()> {
+    if sequences.is_empty() {
+        return Err(PyValueError::new_err("sequences can't be empty"));
+    }
+    if sequences.iter().any(|sequence| sequence.contains(&GAP)) {
+        return Err(PyValueError::new_err("sequences can't contain gaps"));
+    }
+    Ok(())
+}
+
+/// Build the UPGMA guide tree of the sequences from the distances of their pairwise
+/// global alignments and return it in the Newick format.
+#[pyfunction]
+#[pyo3(signature = (sequences, scoring, names=None, threads=None))]
+fn guide_tree(
+    py: Python,
+    sequences: Vec<Vec<u8>>,
+    scoring: Scoring,
+    names: Option<Vec<String>>,
+    threads: Option<usize>,
+) -> PyResult<String> {
+    check_sequences(&sequences)?;
+    let names = MultipleAlignment::from_rows(vec![vec![]; sequences.len()], names)?.names;
+    let threads = resolve_threads(threads)?;
+    let tree = py.allow_threads(|| -> PyResult<GuideTree> {
+        Ok(upgma(distance_matrix(&scoring, &sequences, threads)?))
+    })?;
+    let newick = match &tree {
+        GuideTree::Leaf(index) => names[*index].clone(),
+        GuideTree::Node(..) => {
+            let newick = tree.to_newick(&names, tree.height());
+            newick[..newick.rfind(':').unwrap_or(newick.len())].to_string()
+        }
+    };
+    Ok(format!("{newick};"))
+}
+
+/// Align the sequences progressively: pairwise global alignments give the distances
+/// for the UPGMA guide tree, and the profiles are aligned following the tree. Gap
+/// penalties are affine, so long gaps of the scoring aren't taken into account.
+#[pyfunction]
+#[pyo3(signature = (sequences, scoring, names=None, threads=None))]
+fn progressive_align(
+    py: Python,
+    sequences: Vec<Vec<u8>>,
+    scoring: Scoring,
+    names: Option<Vec<String>>,
+    threads: Option<usize>,
+) -> PyResult<MultipleAlignment> {
+    check_sequences(&sequences)?;
+    let threads = resolve_threads(threads)?;
+    let profile = py.allow_threads(|| -> PyResult<Profile> {
+        let tree = upgma(distance_matrix(&scoring, &sequences, threads)?);
+        Ok(align_tree(&scoring, &tree, &sequences))
+    })?;
+
+    let mut rows = vec![Vec::new(); sequences.len()];
+    for (index, row) in profile.indices.into_iter().zip(profile.rows) {
+        rows[index] = row;
+    }
+    MultipleAlignment::from_rows(rows, names)
+}
+
+#[pymodule]
+pub fn msa(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<MultipleAlignment>()?;
+    m.add_function(wrap_pyfunction!(guide_tree, m)?)?;
+    m.add_function(wrap_pyfunction!(progressive_align, m)?)?;
+    Ok(())
+}
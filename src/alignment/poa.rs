@@ -0,0 +1,793 @@
+// The biggest part of code from this file is copied from https://github.com/rust-bio/rust-bio,
+// because rust-bio keeps operations of partial order alignments private, so they can't be
+// transferred to python. So, it's copied and adapted.
+
+#[rustfmt::skip]
+use bio_types::alignment::{
+    AlignmentMode       as _AlignmentMode,
+    AlignmentOperation  as _AlignmentOperation,
+};
+use bio::alignment::poa::POAGraph;
+use petgraph::dot::Dot;
+use petgraph::graph::NodeIndex;
+use petgraph::visit::Topo;
+use petgraph::{Directed, Graph, Incoming};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::cmp::{max, Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::alignment::alignment_type::rust_bio_alignment_operation_into_py_object;
+use crate::alignment::msa::MultipleAlignment;
+use crate::alignment::scoring::{Scoring, MIN_SCORE};
+
+// Unlike with a total order we may have arbitrary successors in the
+// traceback matrix. Match and Del operations remember In and Out
+// nodes on the reference graph, or come from the first row for source nodes.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum PoaOperation {
+    Match(Option<(usize, usize)>),
+    Del(Option<(usize, usize)>),
+    Ins(Option<usize>),
+    Xclip(usize),
+    Yclip(usize, usize), // to, from
+}
+
+#[derive(Copy, Clone, Debug)]
+struct TracebackCell {
+    score: i32,
+    op: PoaOperation,
+}
+
+impl Ord for TracebackCell {
+    fn cmp(&self, other: &TracebackCell) -> Ordering {
+        self.score.cmp(&other.score)
+    }
+}
+
+impl PartialOrd for TracebackCell {
+    fn partial_cmp(&self, other: &TracebackCell) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for TracebackCell {
+    fn eq(&self, other: &TracebackCell) -> bool {
+        self.score == other.score
+    }
+}
+
+impl Eq for TracebackCell {}
+
+#[derive(Default, Clone, Debug)]
+struct Traceback {
+    cols: usize,
+
+    // store the last visited node in topological order so that
+    // we can index into the end of the alignment when we backtrack
+    last: NodeIndex<usize>,
+    matrix: Vec<Vec<TracebackCell>>,
+}
+
+impl Traceback {
+    /// Create a Traceback matrix for `m` nodes of the graph and the query of length `n`
+    fn with_capacity(m: usize, n: usize) -> Self {
+        Traceback {
+            cols: n,
+            last: NodeIndex::new(0),
+            matrix: vec![Vec::with_capacity(n + 1); m + 1],
+        }
+    }
+
+    /// Populate the first row of the traceback matrix
+    fn initialize_scores(&mut self, gap_open: i32, yclip: i32) {
+        for j in 0..=self.cols {
+            self.matrix[0].push(max(
+                TracebackCell {
+                    score: (j as i32) * gap_open,
+                    op: PoaOperation::Ins(None),
+                },
+                TracebackCell {
+                    score: yclip,
+                    op: PoaOperation::Yclip(0, j),
+                },
+            ));
+        }
+        self.matrix[0][0] = TracebackCell {
+            score: 0,
+            op: PoaOperation::Match(None),
+        };
+    }
+
+    /// Populate the first cell of the row with the best of the deletion and the clip,
+    /// the rest of the row is filled with `MIN_SCORE`
+    fn new_row(&mut self, row: usize, deletion: TracebackCell, xclip: i32) {
+        self.matrix[row].push(max(
+            deletion,
+            TracebackCell {
+                score: xclip,
+                op: PoaOperation::Xclip(0),
+            },
+        ));
+        for _ in 1..=self.cols {
+            self.matrix[row].push(TracebackCell {
+                score: MIN_SCORE,
+                op: PoaOperation::Match(None),
+            });
+        }
+    }
+
+    #[inline(always)]
+    fn set(&mut self, i: usize, j: usize, cell: TracebackCell) {
+        self.matrix[i][j] = cell;
+    }
+
+    #[inline(always)]
+    fn get(&self, i: usize, j: usize) -> TracebackCell {
+        self.matrix[i][j]
+    }
+
+    /// Backtrack through the matrix to construct an optimal path
+    fn operations(&self) -> Vec<(usize, usize, PoaOperation)> {
+        let mut ops: Vec<(usize, usize, PoaOperation)> = vec![];
+
+        let mut i = self.last.index() + 1;
+        let mut j = self.cols;
+
+        while i > 0 || j > 0 {
+            let op = self.get(i, j).op;
+            ops.push((i, j, op));
+            match op {
+                PoaOperation::Match(Some((p, _))) => {
+                    i = p + 1;
+                    j -= 1;
+                }
+                PoaOperation::Del(Some((p, _))) => {
+                    i = p + 1;
+                }
+                PoaOperation::Ins(Some(p)) => {
+                    i = p + 1;
+                    j -= 1;
+                }
+                PoaOperation::Match(None) => {
+                    i = 0;
+                    j -= 1;
+                }
+                PoaOperation::Del(None) => {
+                    i = 0;
+                }
+                PoaOperation::Ins(None) => {
+                    j -= 1;
+                }
+                PoaOperation::Xclip(r) => {
+                    i = r;
+                }
+                PoaOperation::Yclip(r, _) => {
+                    j = r;
+                }
+            }
+        }
+
+        ops.reverse();
+        ops
+    }
+
+    fn score(&self) -> i32 {
+        self.get(self.last.index() + 1, self.cols).score
+    }
+}
+
+/// A partially ordered alignment graph with the scoring used to align sequences against it.
+/// It also keeps the nodes of every added sequence and the nodes aligned to each other,
+/// to build the multiple alignment.
+struct Poa {
+    scoring: Scoring,
+    graph: POAGraph,
+    paths: Vec<Vec<usize>>,
+    aligned: Vec<usize>,
+}
+
+impl Poa {
+    /// Create a new POA graph from an initial reference sequence.
+    fn from_string(scoring: Scoring, seq: &[u8]) -> Self {
+        let mut graph: POAGraph = Graph::with_capacity(seq.len(), seq.len() - 1);
+        let mut prev: NodeIndex<usize> = graph.add_node(seq[0]);
+        let mut node: NodeIndex<usize>;
+        for base in seq.iter().skip(1) {
+            node = graph.add_node(*base);
+            graph.add_edge(prev, node, 1);
+            prev = node;
+        }
+
+        Poa {
+            scoring,
+            paths: vec![(0..seq.len()).collect()],
+            aligned: (0..seq.len()).collect(),
+            graph,
+        }
+    }
+
+    fn add_node(&mut self, base: u8) -> NodeIndex<usize> {
+        let node = self.graph.add_node(base);
+        self.aligned.push(node.index());
+        node
+    }
+
+    /// Return the representative of the nodes aligned to the node.
+    fn aligned_root(&self, mut node: usize) -> usize {
+        while self.aligned[node] != node {
+            node = self.aligned[node];
+        }
+        node
+    }
+
+    fn align_nodes(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.aligned_root(a), self.aligned_root(b));
+        self.aligned[a.max(b)] = a.min(b);
+    }
+
+    /// Return the columns of the nodes: aligned nodes share a column, and columns follow
+    /// the topological order of the graph. When aligned nodes can't be ordered, every
+    /// node gets its own column.
+    fn columns(&self) -> PyResult<(Vec<usize>, usize)> {
+        let groups: Vec<usize> = (0..self.graph.node_count())
+            .map(|node| self.aligned_root(node))
+            .collect();
+        self.order_groups(&groups)
+            .or_else(|| self.order_groups(&(0..groups.len()).collect::<Vec<_>>()))
+            .ok_or_else(|| PyValueError::new_err("The graph has a cycle, so it can't be aligned"))
+    }
+
+    /// Sort groups of nodes topologically, the smallest group first in case of a choice,
+    /// and return the column of every node with the number of columns.
+    fn order_groups(&self, groups: &[usize]) -> Option<(Vec<usize>, usize)> {
+        let count = groups.len();
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut in_degrees = vec![0; count];
+        for edge in self.graph.raw_edges() {
+            let (from, to) = (groups[edge.source().index()], groups[edge.target().index()]);
+            if from != to && !successors[from].contains(&to) {
+                successors[from].push(to);
+                in_degrees[to] += 1;
+            }
+        }
+
+        let mut group_columns = vec![usize::MAX; count];
+        let mut queue: BinaryHeap<Reverse<usize>> = (0..count)
+            .filter(|&group| groups[group] == group && in_degrees[group] == 0)
+            .map(Reverse)
+            .collect();
+        let mut column = 0;
+        while let Some(Reverse(group)) = queue.pop() {
+            group_columns[group] = column;
+            column += 1;
+            for &successor in successors[group].iter() {
+                in_degrees[successor] -= 1;
+                if in_degrees[successor] == 0 {
+                    queue.push(Reverse(successor));
+                }
+            }
+        }
+        let columns: Vec<usize> = groups.iter().map(|&group| group_columns[group]).collect();
+        if columns.contains(&usize::MAX) {
+            None
+        } else {
+            Some((columns, column))
+        }
+    }
+
+    /// Return the gapped rows of the added sequences.
+    fn multiple_alignment(&self) -> PyResult<Vec<Vec<u8>>> {
+        let (columns, count) = self.columns()?;
+        Ok(self
+            .paths
+            .iter()
+            .map(|path| {
+                let mut row = vec![b'-'; count];
+                for &node in path.iter() {
+                    row[columns[node]] = self.graph.raw_nodes()[node].weight;
+                }
+                row
+            })
+            .collect())
+    }
+
+    /// A global Needleman-Wunsch aligner on partially ordered graphs.
+    fn custom(&self, query: &[u8]) -> Traceback {
+        // dimensions of the traceback matrix
+        let (m, n) = (self.graph.node_count(), query.len());
+        // save score location of the max scoring node for the query for suffix clipping
+        let mut max_in_column = vec![(0, 0); n + 1];
+        let mut traceback = Traceback::with_capacity(m, n);
+        traceback.initialize_scores(self.scoring.gap_open, self.scoring.yclip_prefix);
+        // construct the score matrix (O(n^2) space)
+        let mut topo = Topo::new(&self.graph);
+        while let Some(node) = topo.next(&self.graph) {
+            // reference base and index
+            let r = self.graph.raw_nodes()[node.index()].weight; // reference base at previous index
+            let i = node.index() + 1; // 0 index is for initialization so we start at 1
+            traceback.last = node;
+            // iterate over the predecessors of this node
+            let prevs: Vec<NodeIndex<usize>> =
+                self.graph.neighbors_directed(node, Incoming).collect();
+            // the nodes before the query starts are deleted along the edges of the graph
+            let deletion = prevs
+                .iter()
+                .map(|prev_node| TracebackCell {
+                    score: traceback.get(prev_node.index() + 1, 0).score + self.scoring.gap_open,
+                    op: PoaOperation::Del(Some((prev_node.index(), i))),
+                })
+                .max()
+                .unwrap_or(TracebackCell {
+                    score: self.scoring.gap_open,
+                    op: PoaOperation::Del(None),
+                });
+            traceback.new_row(i, deletion, self.scoring.xclip_prefix);
+            // query base and its index in the DAG (traceback matrix rows)
+            for (query_index, query_base) in query.iter().enumerate() {
+                let j = query_index + 1; // 0 index is initialized so we start at 1
+                                         // match and deletion scores for the first reference base
+                let max_cell = if prevs.is_empty() {
+                    TracebackCell {
+                        score: traceback.get(0, j - 1).score
+                            + (self.scoring.match_fn)(r, *query_base),
+                        op: PoaOperation::Match(None),
+                    }
+                } else {
+                    let mut max_cell = max(
+                        TracebackCell {
+                            score: MIN_SCORE,
+                            op: PoaOperation::Match(None),
+                        },
+                        TracebackCell {
+                            score: self.scoring.xclip_prefix,
+                            op: PoaOperation::Xclip(0),
+                        },
+                    );
+                    for prev_node in &prevs {
+                        let i_p: usize = prev_node.index() + 1; // index of previous node
+                        max_cell = max(
+                            max_cell,
+                            max(
+                                TracebackCell {
+                                    score: traceback.get(i_p, j - 1).score
+                                        + (self.scoring.match_fn)(r, *query_base),
+                                    op: PoaOperation::Match(Some((i_p - 1, i - 1))),
+                                },
+                                TracebackCell {
+                                    score: traceback.get(i_p, j).score + self.scoring.gap_open,
+                                    op: PoaOperation::Del(Some((i_p - 1, i))),
+                                },
+                            ),
+                        );
+                    }
+                    max_cell
+                };
+                let score = max(
+                    max_cell,
+                    TracebackCell {
+                        score: traceback.get(i, j - 1).score + self.scoring.gap_open,
+                        op: PoaOperation::Ins(Some(i - 1)),
+                    },
+                );
+                traceback.set(i, j, score);
+                if max_in_column[j].0 < score.score {
+                    max_in_column[j].0 = score.score;
+                    max_in_column[j].1 = i;
+                }
+            }
+        }
+        // X suffix clipping
+        let mut max_in_row = (0, 0);
+        for (j, &(max_score, max_i)) in max_in_column.iter().enumerate() {
+            // avoid pointing to itself, but still take the cell into account for y suffix clipping
+            let maxcell = if max_i == traceback.last.index() + 1 {
+                traceback.get(traceback.last.index() + 1, j)
+            } else {
+                max(
+                    traceback.get(traceback.last.index() + 1, j),
+                    TracebackCell {
+                        score: max_score + self.scoring.xclip_suffix,
+                        op: PoaOperation::Xclip(max_i),
+                    },
+                )
+            };
+            if max_in_row.0 < maxcell.score {
+                max_in_row.0 = maxcell.score;
+                max_in_row.1 = j;
+            }
+            traceback.set(traceback.last.index() + 1, j, maxcell);
+        }
+        // Y suffix clipping from the last node
+        let maxcell = max(
+            traceback.get(traceback.last.index() + 1, n),
+            TracebackCell {
+                score: max_in_row.0 + self.scoring.yclip_suffix,
+                op: PoaOperation::Yclip(max_in_row.1, n),
+            },
+        );
+        if max_in_row.1 != n {
+            traceback.set(traceback.last.index() + 1, n, maxcell);
+        }
+
+        traceback
+    }
+
+    /// Add an edge from `prev` to `node`, or increment the weight of the existing one.
+    fn connect(&mut self, prev: Option<NodeIndex<usize>>, node: NodeIndex<usize>) {
+        let Some(prev) = prev else {
+            return;
+        };
+        match self.graph.find_edge(prev, node) {
+            Some(edge) => *self.graph.edge_weight_mut(edge).unwrap() += 1,
+            None => {
+                self.graph.add_edge(prev, node, 1);
+            }
+        }
+    }
+
+    /// Incorporate a new sequence into a graph from an alignment
+    fn add_alignment(&mut self, operations: &[(usize, usize, PoaOperation)], seq: &[u8]) {
+        // the last node of the sequence in the graph, none before its first symbol
+        let mut prev: Option<NodeIndex<usize>> = None;
+        let mut i: usize = 0;
+        let mut path: Vec<usize> = Vec::with_capacity(seq.len());
+        for &(row, _, op) in operations.iter() {
+            match op {
+                PoaOperation::Match(_) => {
+                    // the matched node is in the row, whether it's a source node or not
+                    let p = row - 1;
+                    let node = if (seq[i] != self.graph.raw_nodes()[p].weight) && (seq[i] != b'X') {
+                        let node = self.add_node(seq[i]);
+                        self.align_nodes(node.index(), p);
+                        node
+                    } else {
+                        NodeIndex::new(p)
+                    };
+                    self.connect(prev, node);
+                    path.push(node.index());
+                    prev = Some(node);
+                    i += 1;
+                }
+                PoaOperation::Ins(_) => {
+                    let node = self.add_node(seq[i]);
+                    self.connect(prev, node);
+                    path.push(node.index());
+                    prev = Some(node);
+                    i += 1;
+                }
+                PoaOperation::Del(_) => {} // we should only have to skip over deleted nodes and xclip
+                PoaOperation::Xclip(_) => {}
+                PoaOperation::Yclip(_, r) => {
+                    i = r;
+                }
+            }
+        }
+        self.paths.push(path);
+    }
+
+    /// Return the consensus sequence generated from the POA graph.
+    fn consensus(&self) -> Vec<u8> {
+        let mut consensus: Vec<u8> = vec![];
+        let max_index = self.graph.node_count();
+        let mut weight_score_next_vec: Vec<(i32, i32, usize)> = vec![(0, 0, 0); max_index];
+        let mut topo = Topo::new(&self.graph);
+        // go through the nodes topologically
+        while let Some(node) = topo.next(&self.graph) {
+            let mut best_weight_score_next: (i32, i32, usize) = (0, 0, usize::MAX);
+            // go through the incoming neighbour nodes
+            for neighbour_node in self.graph.neighbors_directed(node, Incoming) {
+                let neighbour_index = neighbour_node.index();
+                let neighbour_score = weight_score_next_vec[neighbour_index].1;
+                let weight: i32 = self
+                    .graph
+                    .edges_connecting(neighbour_node, node)
+                    .map(|edge| *edge.weight())
+                    .sum();
+                let current_node_score = weight + neighbour_score;
+                // save the neighbour node with the highest weight and score as best
+                if (weight, current_node_score, neighbour_index) > best_weight_score_next {
+                    best_weight_score_next = (weight, current_node_score, neighbour_index);
+                }
+            }
+            weight_score_next_vec[node.index()] = best_weight_score_next;
+        }
+        // get the index of the max scored node (end of consensus)
+        let mut pos = weight_score_next_vec
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &value)| value.1)
+            .map(|(idx, _)| idx)
+            .unwrap();
+        // go through weight_score_next_vec appending to the consensus
+        while pos != usize::MAX {
+            consensus.push(self.graph.raw_nodes()[pos].weight);
+            pos = weight_score_next_vec[pos].2;
+        }
+        consensus.reverse();
+        consensus
+    }
+}
+
+/// One step of a partial order alignment: the position in the query, the node of the graph
+/// and the operation. Clipped parts of the query and the graph aren't included.
+type PoaStep = (Option<usize>, Option<usize>, _AlignmentOperation);
+
+fn make_steps(
+    operations: &[(usize, usize, PoaOperation)],
+    graph: &POAGraph,
+    query: &[u8],
+) -> Vec<PoaStep> {
+    operations
+        .iter()
+        .filter_map(|&(i, j, op)| match op {
+            PoaOperation::Match(_) => {
+                let node = i - 1;
+                let operation = if graph.raw_nodes()[node].weight == query[j - 1] {
+                    _AlignmentOperation::Match
+                } else {
+                    _AlignmentOperation::Subst
+                };
+                Some((Some(j - 1), Some(node), operation))
+            }
+            PoaOperation::Ins(_) => Some((Some(j - 1), None, _AlignmentOperation::Ins)),
+            PoaOperation::Del(_) => Some((None, Some(i - 1), _AlignmentOperation::Del)),
+            PoaOperation::Xclip(_) | PoaOperation::Yclip(_, _) => None,
+        })
+        .collect()
+}
+
+/// Alignment of a query against the partial order graph. The query plays the role of `x`,
+/// so `Ins` consumes the query and `Del` consumes the nodes of the graph.
+#[pyclass]
+pub struct PoaAlignment {
+    score: i32,
+    steps: Vec<PoaStep>,
+    mode: _AlignmentMode,
+}
+
+#[pymethods]
+impl PoaAlignment {
+    #[getter]
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    #[getter]
+    pub fn x_start(&self) -> usize {
+        self.steps.iter().find_map(|step| step.0).unwrap_or(0)
+    }
+
+    #[getter]
+    pub fn x_end(&self) -> usize {
+        self.steps
+            .iter()
+            .rev()
+            .find_map(|step| step.0.map(|i| i + 1))
+            .unwrap_or(0)
+    }
+
+    #[getter]
+    pub fn operations(&self, py: Python) -> Vec<Option<PyObject>> {
+        self.steps
+            .iter()
+            .map(|step| rust_bio_alignment_operation_into_py_object(step.2, py))
+            .collect()
+    }
+
+    #[getter]
+    pub fn nodes(&self) -> Vec<usize> {
+        self.steps.iter().filter_map(|step| step.1).collect()
+    }
+
+    #[getter]
+    pub fn mode(&self) -> &str {
+        match self.mode {
+            _AlignmentMode::Local => "local",
+            _AlignmentMode::Semiglobal => "semiglobal",
+            _AlignmentMode::Global => "global",
+            _AlignmentMode::Custom => "custom",
+        }
+    }
+
+    pub fn path(&self, py: Python) -> Vec<(Option<usize>, Option<usize>, Option<PyObject>)> {
+        self.steps
+            .iter()
+            .map(|&(x, node, operation)| {
+                (
+                    x,
+                    node,
+                    rust_bio_alignment_operation_into_py_object(operation, py),
+                )
+            })
+            .collect()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "<PoaAlignment: score={}, x_start={}, x_end={}, mode={}>",
+            self.score,
+            self.x_start(),
+            self.x_end(),
+            self.mode()
+        )
+    }
+}
+
+#[pyclass]
+pub struct PoaGraph(POAGraph);
+
+#[pymethods]
+impl PoaGraph {
+    #[getter]
+    pub fn node_count(&self) -> usize {
+        self.0.node_count()
+    }
+
+    #[getter]
+    pub fn edge_count(&self) -> usize {
+        self.0.edge_count()
+    }
+
+    #[getter]
+    pub fn nodes<'p>(&self, py: Python<'p>) -> &'p PyBytes {
+        let v: Vec<u8> = self.0.raw_nodes().iter().map(|node| node.weight).collect();
+        PyBytes::new(py, v.as_slice())
+    }
+
+    #[getter]
+    pub fn edges(&self) -> Vec<(usize, usize, i32)> {
+        self.0
+            .raw_edges()
+            .iter()
+            .map(|edge| (edge.source().index(), edge.target().index(), edge.weight))
+            .collect()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let graph: Graph<char, i32, Directed, usize> =
+            self.0.map(|_, base| char::from(*base), |_, weight| *weight);
+        format!("{}", Dot::new(&graph))
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "<PoaGraph: node_count={}, edge_count={}>",
+            self.0.node_count(),
+            self.0.edge_count()
+        )
+    }
+}
+
+/// Partial order aligner. Note that the graph alignment uses linear gap penalties,
+/// so only `gap_open` of the scoring is taken into account.
+#[pyclass]
+pub struct PoaAligner {
+    traceback: Traceback,
+    query: Vec<u8>,
+    poa: Poa,
+}
+
+impl PoaAligner {
+    fn calculate_alignment(
+        &mut self,
+        query: &[u8],
+        clip_penalties: Option<[i32; 4]>,
+        mode: _AlignmentMode,
+    ) -> PoaAlignment {
+        // Store the current clip penalties
+        let original_clip_penalties = [
+            self.poa.scoring.xclip_prefix,
+            self.poa.scoring.xclip_suffix,
+            self.poa.scoring.yclip_prefix,
+            self.poa.scoring.yclip_suffix,
+        ];
+
+        // Temporarily Over-write the clip penalties
+        if let Some(clip_penalties) = clip_penalties {
+            self.poa.scoring.xclip_prefix = clip_penalties[0];
+            self.poa.scoring.xclip_suffix = clip_penalties[1];
+            self.poa.scoring.yclip_prefix = clip_penalties[2];
+            self.poa.scoring.yclip_suffix = clip_penalties[3];
+        }
+
+        self.query = query.to_vec();
+        self.traceback = self.poa.custom(query);
+
+        // Set the clip penalties to the original values
+        self.poa.scoring.xclip_prefix = original_clip_penalties[0];
+        self.poa.scoring.xclip_suffix = original_clip_penalties[1];
+        self.poa.scoring.yclip_prefix = original_clip_penalties[2];
+        self.poa.scoring.yclip_suffix = original_clip_penalties[3];
+
+        PoaAlignment {
+            score: self.traceback.score(),
+            steps: make_steps(&self.traceback.operations(), &self.poa.graph, query),
+            mode,
+        }
+    }
+}
+
+#[pymethods]
+impl PoaAligner {
+    #[new]
+    pub fn new(scoring: Scoring, reference: &[u8]) -> PyResult<Self> {
+        if reference.is_empty() {
+            return Err(PyValueError::new_err("reference can't be empty"));
+        }
+        if !scoring.has_plain_gaps() {
+            return Err(PyValueError::new_err(
+                "PoaAligner supports only the same gap penalties for insertions and deletions",
+            ));
+        }
+
+        Ok(PoaAligner {
+            traceback: Traceback::default(),
+            query: reference.to_vec(),
+            poa: Poa::from_string(scoring, reference),
+        })
+    }
+
+    pub fn calculate_custom(&mut self, query: &[u8]) -> PoaAlignment {
+        self.calculate_alignment(query, None, _AlignmentMode::Custom)
+    }
+
+    pub fn calculate_global(&mut self, query: &[u8]) -> PoaAlignment {
+        self.calculate_alignment(
+            query,
+            Some([MIN_SCORE, MIN_SCORE, MIN_SCORE, MIN_SCORE]),
+            _AlignmentMode::Global,
+        )
+    }
+
+    pub fn calculate_semiglobal(&mut self, query: &[u8]) -> PoaAlignment {
+        self.calculate_alignment(
+            query,
+            Some([MIN_SCORE, MIN_SCORE, 0, 0]),
+            _AlignmentMode::Semiglobal,
+        )
+    }
+
+    pub fn calculate_local(&mut self, query: &[u8]) -> PoaAlignment {
+        self.calculate_alignment(query, Some([0, 0, 0, 0]), _AlignmentMode::Local)
+    }
+
+    /// Add the last aligned query to the graph.
+    pub fn add_to_graph(&mut self) -> PyResult<()> {
+        if self.traceback.matrix.is_empty() {
+            return Err(PyValueError::new_err(
+                "Nothing to add, calculate an alignment first",
+            ));
+        }
+        let operations = self.traceback.operations();
+        self.poa.add_alignment(&operations, &self.query);
+        self.traceback = Traceback::default();
+        Ok(())
+    }
+
+    pub fn consensus<'p>(&self, py: Python<'p>) -> &'p PyBytes {
+        PyBytes::new(py, self.poa.consensus().as_slice())
+    }
+
+    /// Return the multiple alignment of the reference and the sequences added to the graph.
+    /// Symbols aligned to the same node, or substituted for it, share a column. Clipped
+    /// parts of the sequences aren't in the graph, so they aren't in the alignment either.
+    #[pyo3(signature = (names=None))]
+    pub fn multiple_alignment(&self, names: Option<Vec<String>>) -> PyResult<MultipleAlignment> {
+        MultipleAlignment::from_rows(self.poa.multiple_alignment()?, names)
+    }
+
+    #[getter]
+    pub fn graph(&self) -> PoaGraph {
+        PoaGraph(self.poa.graph.clone())
+    }
+}
+
+#[pymodule]
+pub fn poa(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PoaAligner>()?;
+    m.add_class::<PoaAlignment>()?;
+    m.add_class::<PoaGraph>()?;
+    Ok(())
+}
@@ -8,20 +8,33 @@ use bio_types::alignment::{
     AlignmentMode       as _AlignmentMode,
     AlignmentOperation  as _AlignmentOperation,
 };
+use bio::alignment::pairwise::Scoring as _Scoring;
 use bio::scores::{blosum62, pam120, pam200, pam250, pam40};
-use pyo3::exceptions::PyValueError;
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::{PyTypeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyType;
+use pyo3::types::{PyBytes, PyType};
 use std::cmp::max;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::iter::repeat;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::thread;
 
-use crate::alignment::alignment_type::Alignment;
+use crate::alignment::alignment_type::{parse_alignment_mode, Alignment};
+use crate::alignment::convex;
+use crate::alignment::striped;
+use crate::alignment::xdrop;
+use crate::utils::{hash, richcmp, run_parallel_with};
 
 /// Value to use as a 'negative infinity' score. Should be close to `i32::MIN`,
 /// but avoid underflow when used with reasonable scoring parameters or even
 /// adding two negative infinities. Use ~ `0.4 * i32::MIN`
-const MIN_SCORE: i32 = -858_993_459;
+pub const MIN_SCORE: i32 = -858_993_459;
+
+/// Match function type accepted by rust-bio's generic aligners.
+pub type BoxedMatchFn = Box<dyn Fn(u8, u8) -> i32 + Send + Sync + 'static>;
 
 fn make_match_score_function(match_score: i32, mismatch_score: i32) -> impl Fn(u8, u8) -> i32 {
     move |a: u8, b: u8| -> i32 {
@@ -33,16 +46,246 @@ fn make_match_score_function(match_score: i32, mismatch_score: i32) -> impl Fn(u
     }
 }
 
+/// Number of cells in a match table covering every pair of bytes.
+const MATCH_TABLE_SIZE: usize = 256 * 256;
+
+fn make_match_table_function(table: Arc<[i32]>) -> impl Fn(u8, u8) -> i32 {
+    move |a: u8, b: u8| -> i32 { table[(a as usize) << 8 | b as usize] }
+}
+
+/// Scores of all byte pairs with their hash computed once, so scorings built from tables
+/// are compared and hashed without going through the whole table every time.
 #[derive(Clone)]
-#[pyclass]
+struct MatchTable {
+    scores: Arc<[i32]>,
+    hash: u64,
+}
+
+impl MatchTable {
+    fn new(scores: Vec<i32>) -> Self {
+        MatchTable {
+            hash: hash(&scores),
+            scores: scores.into(),
+        }
+    }
+}
+
+impl PartialEq for MatchTable {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.scores == other.scores
+    }
+}
+
+impl Hash for MatchTable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+fn gap_score(gap_open: i32, gap_extend: i32, long_gap: Option<(i32, i32)>, len: usize) -> i32 {
+    if len == 0 {
+        return 0;
+    }
+    let score = gap_open + gap_extend * len as i32;
+    long_gap.map_or(score, |(gap_open, gap_extend)| {
+        max(score, gap_open + gap_extend * len as i32)
+    })
+}
+
+fn check_gap_penalties(gap_open: i32, gap_extend: i32) -> PyResult<()> {
+    if gap_open > 0 {
+        return Err(PyValueError::new_err("gap_open can't be positive"));
+    }
+
+    if gap_extend > 0 {
+        return Err(PyValueError::new_err("gap_extend can't be positive"));
+    }
+
+    Ok(())
+}
+
+fn check_clip_penalty(name: &str, penalty: i32) -> PyResult<()> {
+    if penalty > 0 {
+        return Err(PyValueError::new_err(format!("{name} can't be positive")));
+    }
+
+    if penalty < MIN_SCORE {
+        return Err(PyValueError::new_err(format!(
+            "{name} can't be less than MIN_SCORE"
+        )));
+    }
+
+    Ok(())
+}
+
+fn check_matrix_symbol(symbol: char) -> PyResult<u8> {
+    if symbol.is_ascii() {
+        Ok(symbol as u8)
+    } else {
+        Err(PyValueError::new_err(format!(
+            "Matrix symbol {symbol:?} is not an ASCII character"
+        )))
+    }
+}
+
+/// Build the lookup table of a substitution matrix. Pairs which are not in the matrix
+/// fall back to the same pair in the other letter case (so that `a`/`A` are scored
+/// equally) and then to `default`, which is the lowest score of the matrix if not given.
+fn make_match_table(entries: &[(u8, u8, i32)], default: Option<i32>) -> PyResult<Vec<i32>> {
+    let default = match default.or_else(|| entries.iter().map(|&(_, _, score)| score).min()) {
+        Some(default) => default,
+        None => return Err(PyValueError::new_err("Matrix can't be empty")),
+    };
+
+    let mut defined = vec![false; MATCH_TABLE_SIZE];
+    let mut table = vec![default; MATCH_TABLE_SIZE];
+    for &(a, b, score) in entries {
+        let index = (a as usize) << 8 | b as usize;
+        table[index] = score;
+        defined[index] = true;
+    }
+
+    for a in 0..=255u8 {
+        for b in 0..=255u8 {
+            let index = (a as usize) << 8 | b as usize;
+            if defined[index] {
+                continue;
+            }
+            for (ca, cb) in [
+                (a.to_ascii_uppercase(), b.to_ascii_uppercase()),
+                (a.to_ascii_lowercase(), b.to_ascii_lowercase()),
+            ] {
+                let case_index = (ca as usize) << 8 | cb as usize;
+                if defined[case_index] {
+                    table[index] = table[case_index];
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(table)
+}
+
+/// Collect the entries of a square matrix whose rows and columns are labeled by `alphabet`.
+fn make_matrix_entries(alphabet: &[u8], matrix: &[Vec<i32>]) -> PyResult<Vec<(u8, u8, i32)>> {
+    if matrix.len() != alphabet.len() || matrix.iter().any(|row| row.len() != alphabet.len()) {
+        return Err(PyValueError::new_err(format!(
+            "Matrix must be {0}x{0} to match the alphabet",
+            alphabet.len()
+        )));
+    }
+    for (i, a) in alphabet.iter().enumerate() {
+        if alphabet[..i].contains(a) {
+            return Err(PyValueError::new_err(format!(
+                "Alphabet contains duplicate symbol {:?}",
+                *a as char
+            )));
+        }
+    }
+
+    Ok(alphabet
+        .iter()
+        .zip(matrix.iter())
+        .flat_map(|(&a, row)| {
+            alphabet
+                .iter()
+                .zip(row.iter())
+                .map(move |(&b, &score)| (a, b, score))
+        })
+        .collect())
+}
+
+/// Parse a substitution matrix in the NCBI format (as distributed with BLAST, e.g. BLOSUM45
+/// or NUC.4.4): `#` comments, a header line with column symbols and one line per row
+/// starting with its symbol.
+fn parse_ncbi_matrix(text: &str) -> PyResult<Vec<(u8, u8, i32)>> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let columns = match lines.next() {
+        Some(header) => header
+            .split_whitespace()
+            .map(|symbol| match symbol.as_bytes() {
+                [b] if b.is_ascii() => Ok(*b),
+                _ => Err(PyValueError::new_err(format!(
+                    "Incorrect matrix column symbol {symbol:?}"
+                ))),
+            })
+            .collect::<PyResult<Vec<u8>>>()?,
+        None => return Err(PyValueError::new_err("Matrix can't be empty")),
+    };
+
+    let mut alphabet = Vec::with_capacity(columns.len());
+    let mut matrix = Vec::with_capacity(columns.len());
+    for line in lines {
+        let mut fields = line.split_whitespace();
+        let symbol = fields.next().unwrap_or_default();
+        match symbol.as_bytes() {
+            [b] if b.is_ascii() => alphabet.push(*b),
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "Incorrect matrix row symbol {symbol:?}"
+                )))
+            }
+        }
+        let row = fields
+            .map(|field| {
+                field
+                    .parse::<i32>()
+                    .map_err(|_| PyValueError::new_err(format!("Incorrect matrix score {field:?}")))
+            })
+            .collect::<PyResult<Vec<i32>>>()?;
+        matrix.push(row);
+    }
+
+    if alphabet != columns {
+        return Err(PyValueError::new_err(
+            "Matrix rows must be labeled with the same symbols as columns",
+        ));
+    }
+    make_matrix_entries(&alphabet, &matrix)
+}
+
+#[derive(Clone)]
+#[pyclass(module = "bioforma.alignment")]
 pub struct Scoring {
+    #[pyo3(get)]
     pub gap_open: i32,
+    #[pyo3(get)]
     pub gap_extend: i32,
+    /// Penalties of gaps in y, i.e. insertions of x symbols. Equal to `gap_open` and
+    /// `gap_extend` unless set with `with_gaps`.
+    #[pyo3(get)]
+    pub ins_gap_open: i32,
+    #[pyo3(get)]
+    pub ins_gap_extend: i32,
+    /// Penalties of gaps in x, i.e. deletions of y symbols.
+    #[pyo3(get)]
+    pub del_gap_open: i32,
+    #[pyo3(get)]
+    pub del_gap_extend: i32,
+    /// Second `(gap_open, gap_extend)` pair for dual-affine gaps: a gap scores the best
+    /// of both pairs, so long gaps may be cheaper to extend.
+    #[pyo3(get)]
+    pub long_gap: Option<(i32, i32)>,
     pub match_fn: Arc<dyn Fn(u8, u8) -> i32 + Send + Sync + 'static>,
+    #[pyo3(get)]
     pub match_scores: Option<(i32, i32)>,
+    /// Name of the built-in substitution matrix. These matrices are defined only for
+    /// letters, so they're pickled and compared by name instead of the match table.
+    matrix: Option<&'static str>,
+    /// Scores of scorings created from tables, kept to pickle, compare and hash them.
+    table: Option<MatchTable>,
+    #[pyo3(get)]
     pub xclip_prefix: i32,
+    #[pyo3(get)]
     pub xclip_suffix: i32,
+    #[pyo3(get)]
     pub yclip_prefix: i32,
+    #[pyo3(get)]
     pub yclip_suffix: i32,
 }
 
@@ -50,28 +293,29 @@ pub struct Scoring {
 impl Scoring {
     #[new]
     pub fn new(gap_open: i32, gap_extend: i32, match_func: &str) -> PyResult<Self> {
-        if gap_open > 0 {
-            return Err(PyValueError::new_err("gap_open can't be positive"));
-        }
-
-        if gap_open > 0 {
-            return Err(PyValueError::new_err("gap_extend can't be positive"));
-        }
-
-        let func: PyResult<fn(u8, u8) -> i32> = match match_func {
-            "blosum62" => Ok(blosum62),
-            "pam120" => Ok(pam120),
-            "pam200" => Ok(pam200),
-            "pam250" => Ok(pam250),
-            "pam40" => Ok(pam40),
-            _ => Err(PyValueError::new_err("Incorrect mathc_func")),
+        check_gap_penalties(gap_open, gap_extend)?;
+
+        let (matrix, func): (&'static str, fn(u8, u8) -> i32) = match match_func {
+            "blosum62" => ("blosum62", blosum62),
+            "pam120" => ("pam120", pam120),
+            "pam200" => ("pam200", pam200),
+            "pam250" => ("pam250", pam250),
+            "pam40" => ("pam40", pam40),
+            _ => return Err(PyValueError::new_err("Incorrect mathc_func")),
         };
 
         Ok(Scoring {
             gap_open,
             gap_extend,
-            match_fn: Arc::new(func?),
+            ins_gap_open: gap_open,
+            ins_gap_extend: gap_extend,
+            del_gap_open: gap_open,
+            del_gap_extend: gap_extend,
+            long_gap: None,
+            match_fn: Arc::new(func),
             match_scores: None,
+            matrix: Some(matrix),
+            table: None,
             xclip_prefix: MIN_SCORE,
             xclip_suffix: MIN_SCORE,
             yclip_prefix: MIN_SCORE,
@@ -87,13 +331,7 @@ impl Scoring {
         match_score: i32,
         mismatch_score: i32,
     ) -> PyResult<Self> {
-        if gap_open > 0 {
-            return Err(PyValueError::new_err("gap_open can't be positive"));
-        }
-
-        if gap_open > 0 {
-            return Err(PyValueError::new_err("gap_extend can't be positive"));
-        }
+        check_gap_penalties(gap_open, gap_extend)?;
 
         if match_score < 0 {
             return Err(PyValueError::new_err("match_score can't be negative"));
@@ -106,14 +344,417 @@ impl Scoring {
         Ok(Scoring {
             gap_open,
             gap_extend,
+            ins_gap_open: gap_open,
+            ins_gap_extend: gap_extend,
+            del_gap_open: gap_open,
+            del_gap_extend: gap_extend,
+            long_gap: None,
             match_fn: Arc::new(make_match_score_function(match_score, mismatch_score)),
             match_scores: Some((match_score, mismatch_score)),
+            matrix: None,
+            table: None,
+            xclip_prefix: MIN_SCORE,
+            xclip_suffix: MIN_SCORE,
+            yclip_prefix: MIN_SCORE,
+            yclip_suffix: MIN_SCORE,
+        })
+    }
+
+    /// Create scoring from a Python callable `func(a, b) -> int` taking two byte values.
+    /// The callable is evaluated once for every pair of bytes, so alignment itself
+    /// never calls back into Python.
+    #[classmethod]
+    pub fn from_function(
+        _cls: &PyType,
+        gap_open: i32,
+        gap_extend: i32,
+        func: &PyAny,
+    ) -> PyResult<Self> {
+        if !func.is_callable() {
+            return Err(PyTypeError::new_err("func must be callable"));
+        }
+
+        let mut table = Vec::with_capacity(MATCH_TABLE_SIZE);
+        for a in 0..=255u8 {
+            for b in 0..=255u8 {
+                table.push(func.call1((a, b))?.extract::<i32>()?);
+            }
+        }
+        Self::from_match_table(gap_open, gap_extend, table)
+    }
+
+    /// Create scoring from a substitution matrix given as nested dicts, where
+    /// `matrix[a][b]` is the score of aligning `a` from `x` against `b` from `y`.
+    #[classmethod]
+    #[pyo3(signature = (gap_open, gap_extend, matrix, default=None))]
+    pub fn from_matrix(
+        _cls: &PyType,
+        gap_open: i32,
+        gap_extend: i32,
+        matrix: HashMap<char, HashMap<char, i32>>,
+        default: Option<i32>,
+    ) -> PyResult<Self> {
+        let mut entries = Vec::new();
+        for (a, row) in matrix.into_iter() {
+            let a = check_matrix_symbol(a)?;
+            for (b, score) in row.into_iter() {
+                entries.push((a, check_matrix_symbol(b)?, score));
+            }
+        }
+        Self::from_match_table(gap_open, gap_extend, make_match_table(&entries, default)?)
+    }
+
+    /// Create scoring from a square substitution matrix whose rows and columns are
+    /// labeled by `alphabet`.
+    #[classmethod]
+    #[pyo3(signature = (gap_open, gap_extend, alphabet, matrix, default=None))]
+    pub fn from_matrix_list(
+        _cls: &PyType,
+        gap_open: i32,
+        gap_extend: i32,
+        alphabet: &[u8],
+        matrix: Vec<Vec<i32>>,
+        default: Option<i32>,
+    ) -> PyResult<Self> {
+        let entries = make_matrix_entries(alphabet, &matrix)?;
+        Self::from_match_table(gap_open, gap_extend, make_match_table(&entries, default)?)
+    }
+
+    /// Create scoring from a substitution matrix file in the NCBI format.
+    #[classmethod]
+    #[pyo3(signature = (gap_open, gap_extend, path, default=None))]
+    pub fn from_matrix_file(
+        _cls: &PyType,
+        gap_open: i32,
+        gap_extend: i32,
+        path: PathBuf,
+        default: Option<i32>,
+    ) -> PyResult<Self> {
+        let entries = parse_ncbi_matrix(&std::fs::read_to_string(path)?)?;
+        Self::from_match_table(gap_open, gap_extend, make_match_table(&entries, default)?)
+    }
+
+    /// Return a copy of the scoring with the given gap penalties of insertions (gaps in y)
+    /// and deletions (gaps in x) replaced.
+    #[pyo3(signature = (ins_gap_open=None, ins_gap_extend=None, del_gap_open=None, del_gap_extend=None))]
+    pub fn with_gaps(
+        &self,
+        ins_gap_open: Option<i32>,
+        ins_gap_extend: Option<i32>,
+        del_gap_open: Option<i32>,
+        del_gap_extend: Option<i32>,
+    ) -> PyResult<Self> {
+        let mut scoring = self.clone();
+        for (name, penalty, field) in [
+            ("ins_gap_open", ins_gap_open, &mut scoring.ins_gap_open),
+            (
+                "ins_gap_extend",
+                ins_gap_extend,
+                &mut scoring.ins_gap_extend,
+            ),
+            ("del_gap_open", del_gap_open, &mut scoring.del_gap_open),
+            (
+                "del_gap_extend",
+                del_gap_extend,
+                &mut scoring.del_gap_extend,
+            ),
+        ] {
+            if let Some(penalty) = penalty {
+                if penalty > 0 {
+                    return Err(PyValueError::new_err(format!("{name} can't be positive")));
+                }
+                *field = penalty;
+            }
+        }
+        Ok(scoring)
+    }
+
+    /// Return a copy of the scoring with dual-affine gaps: a gap of length `l` scores
+    /// the best of the regular penalties and `gap_open + gap_extend * l`. Usually the
+    /// second pair has a larger open and a smaller extend penalty to model long gaps.
+    pub fn with_long_gap(&self, gap_open: i32, gap_extend: i32) -> PyResult<Self> {
+        check_gap_penalties(gap_open, gap_extend)?;
+        let mut scoring = self.clone();
+        scoring.long_gap = Some((gap_open, gap_extend));
+        Ok(scoring)
+    }
+
+    /// Return a copy of the scoring with both x clip penalties set to `penalty`.
+    pub fn xclip(&self, penalty: i32) -> PyResult<Self> {
+        self.with_clip(Some(penalty), Some(penalty), None, None)
+    }
+
+    /// Return a copy of the scoring with both y clip penalties set to `penalty`.
+    pub fn yclip(&self, penalty: i32) -> PyResult<Self> {
+        self.with_clip(None, None, Some(penalty), Some(penalty))
+    }
+
+    /// Return a copy of the scoring with the given clip penalties replaced. Use
+    /// `MIN_SCORE` to disallow clipping again.
+    #[pyo3(signature = (xclip_prefix=None, xclip_suffix=None, yclip_prefix=None, yclip_suffix=None))]
+    pub fn with_clip(
+        &self,
+        xclip_prefix: Option<i32>,
+        xclip_suffix: Option<i32>,
+        yclip_prefix: Option<i32>,
+        yclip_suffix: Option<i32>,
+    ) -> PyResult<Self> {
+        let mut scoring = self.clone();
+        for (name, penalty, field) in [
+            ("xclip_prefix", xclip_prefix, &mut scoring.xclip_prefix),
+            ("xclip_suffix", xclip_suffix, &mut scoring.xclip_suffix),
+            ("yclip_prefix", yclip_prefix, &mut scoring.yclip_prefix),
+            ("yclip_suffix", yclip_suffix, &mut scoring.yclip_suffix),
+        ] {
+            if let Some(penalty) = penalty {
+                check_clip_penalty(name, penalty)?;
+                *field = penalty;
+            }
+        }
+        Ok(scoring)
+    }
+
+    /// Restore the scoring pickled by `__reduce__`. The match function is given by the
+    /// name of the built-in `matrix`, by `match_scores` or by the table of all byte pairs
+    /// as little-endian `i32`s.
+    #[staticmethod]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (gap_open, gap_extend, gaps, long_gap, matrix, match_scores, table, xclip_prefix, xclip_suffix, yclip_prefix, yclip_suffix))]
+    fn _from_state(
+        py: Python,
+        gap_open: i32,
+        gap_extend: i32,
+        gaps: (i32, i32, i32, i32),
+        long_gap: Option<(i32, i32)>,
+        matrix: Option<&str>,
+        match_scores: Option<(i32, i32)>,
+        table: Option<&[u8]>,
+        xclip_prefix: i32,
+        xclip_suffix: i32,
+        yclip_prefix: i32,
+        yclip_suffix: i32,
+    ) -> PyResult<Self> {
+        let cls = py.get_type::<Self>();
+        let scoring = match (matrix, match_scores, table) {
+            (Some(matrix), _, _) => Self::new(gap_open, gap_extend, matrix)?,
+            (None, Some((match_score, mismatch_score)), _) => {
+                Self::from_scores(cls, gap_open, gap_extend, match_score, mismatch_score)?
+            }
+            (None, None, Some(table)) if table.len() == MATCH_TABLE_SIZE * 4 => {
+                let table = table
+                    .chunks_exact(4)
+                    .map(|chunk| i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect();
+                Self::from_match_table(gap_open, gap_extend, table)?
+            }
+            _ => return Err(PyValueError::new_err("Invalid scoring state")),
+        };
+        let mut scoring =
+            scoring.with_gaps(Some(gaps.0), Some(gaps.1), Some(gaps.2), Some(gaps.3))?;
+        if let Some((gap_open, gap_extend)) = long_gap {
+            scoring = scoring.with_long_gap(gap_open, gap_extend)?;
+        }
+        scoring.with_clip(
+            Some(xclip_prefix),
+            Some(xclip_suffix),
+            Some(yclip_prefix),
+            Some(yclip_suffix),
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'p>(
+        slf: &'p PyCell<Self>,
+        py: Python<'p>,
+    ) -> PyResult<(
+        &'p PyAny,
+        (
+            i32,
+            i32,
+            (i32, i32, i32, i32),
+            Option<(i32, i32)>,
+            Option<&'static str>,
+            Option<(i32, i32)>,
+            Option<&'p PyBytes>,
+            i32,
+            i32,
+            i32,
+            i32,
+        ),
+    )> {
+        let scoring = slf.borrow();
+        let table = scoring.table.as_ref().map(|table| {
+            let bytes: Vec<u8> = table.scores.iter().flat_map(|s| s.to_le_bytes()).collect();
+            PyBytes::new(py, &bytes)
+        });
+        Ok((
+            slf.get_type().getattr("_from_state")?,
+            (
+                scoring.gap_open,
+                scoring.gap_extend,
+                scoring.gaps(),
+                scoring.long_gap,
+                scoring.matrix,
+                scoring.match_scores,
+                table,
+                scoring.xclip_prefix,
+                scoring.xclip_suffix,
+                scoring.yclip_prefix,
+                scoring.yclip_suffix,
+            ),
+        ))
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(&self.key())
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
+        richcmp(self.key(), other.key(), op)
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        self.clone()
+    }
+
+    pub fn __repr__(&self) -> String {
+        let match_scores = match self.match_scores {
+            Some(scores) => format!("{scores:?}"),
+            None => "None".into(),
+        };
+        let mut gaps = String::new();
+        if self.gaps()
+            != (
+                self.gap_open,
+                self.gap_extend,
+                self.gap_open,
+                self.gap_extend,
+            )
+        {
+            gaps.push_str(&format!(
+                ", ins_gap_open={}, ins_gap_extend={}, del_gap_open={}, del_gap_extend={}",
+                self.ins_gap_open, self.ins_gap_extend, self.del_gap_open, self.del_gap_extend
+            ));
+        }
+        if let Some(long_gap) = self.long_gap {
+            gaps.push_str(&format!(", long_gap={long_gap:?}"));
+        }
+        format!(
+            "<Scoring: gap_open={}, gap_extend={}{}, match_scores={}, xclip_prefix={}, xclip_suffix={}, yclip_prefix={}, yclip_suffix={}>",
+            self.gap_open, self.gap_extend, gaps, match_scores, self.xclip_prefix, self.xclip_suffix, self.yclip_prefix, self.yclip_suffix
+        )
+    }
+}
+
+impl Scoring {
+    /// Everything that defines the scoring, used for comparison and hashing. Built-in
+    /// matrices are represented by their names and tables by their cached hashes.
+    #[allow(clippy::type_complexity)]
+    fn key(
+        &self,
+    ) -> (
+        [i32; 10],
+        Option<(i32, i32)>,
+        Option<&'static str>,
+        Option<(i32, i32)>,
+        Option<&MatchTable>,
+    ) {
+        let penalties = [
+            self.gap_open,
+            self.gap_extend,
+            self.ins_gap_open,
+            self.ins_gap_extend,
+            self.del_gap_open,
+            self.del_gap_extend,
+            self.xclip_prefix,
+            self.xclip_suffix,
+            self.yclip_prefix,
+            self.yclip_suffix,
+        ];
+        (
+            penalties,
+            self.long_gap,
+            self.matrix,
+            self.match_scores,
+            self.table.as_ref(),
+        )
+    }
+
+    fn gaps(&self) -> (i32, i32, i32, i32) {
+        (
+            self.ins_gap_open,
+            self.ins_gap_extend,
+            self.del_gap_open,
+            self.del_gap_extend,
+        )
+    }
+
+    /// Score of a gap in y of `len` symbols of x, zero if `len` is zero.
+    pub fn ins_gap(&self, len: usize) -> i32 {
+        gap_score(self.ins_gap_open, self.ins_gap_extend, self.long_gap, len)
+    }
+
+    /// Score of a gap in x of `len` symbols of y, zero if `len` is zero.
+    pub fn del_gap(&self, len: usize) -> i32 {
+        gap_score(self.del_gap_open, self.del_gap_extend, self.long_gap, len)
+    }
+
+    /// Whether gaps are affine with the same penalties in both directions, which is
+    /// the only gap model supported by rust-bio's aligners and the fast paths.
+    pub fn has_plain_gaps(&self) -> bool {
+        self.long_gap.is_none()
+            && self.gaps()
+                == (
+                    self.gap_open,
+                    self.gap_extend,
+                    self.gap_open,
+                    self.gap_extend,
+                )
+    }
+
+    fn from_match_table(gap_open: i32, gap_extend: i32, table: Vec<i32>) -> PyResult<Self> {
+        check_gap_penalties(gap_open, gap_extend)?;
+
+        let table = MatchTable::new(table);
+        Ok(Scoring {
+            gap_open,
+            gap_extend,
+            ins_gap_open: gap_open,
+            ins_gap_extend: gap_extend,
+            del_gap_open: gap_open,
+            del_gap_extend: gap_extend,
+            long_gap: None,
+            match_fn: Arc::new(make_match_table_function(Arc::clone(&table.scores))),
+            match_scores: None,
+            matrix: None,
+            table: Some(table),
             xclip_prefix: MIN_SCORE,
             xclip_suffix: MIN_SCORE,
             yclip_prefix: MIN_SCORE,
             yclip_suffix: MIN_SCORE,
         })
     }
+
+    /// Convert into rust-bio's `Scoring` to use it with rust-bio's aligners directly.
+    /// rust-bio uses `gap_open` and `gap_extend` for both insertions and deletions, so
+    /// check `has_plain_gaps` first.
+    pub fn to_rust_bio_scoring(&self) -> _Scoring<BoxedMatchFn> {
+        let match_fn = Arc::clone(&self.match_fn);
+        _Scoring {
+            gap_open: self.gap_open,
+            gap_extend: self.gap_extend,
+            match_fn: Box::new(move |a: u8, b: u8| match_fn(a, b)),
+            match_scores: self.match_scores,
+            xclip_prefix: self.xclip_prefix,
+            xclip_suffix: self.xclip_suffix,
+            yclip_prefix: self.yclip_prefix,
+            yclip_suffix: self.yclip_suffix,
+        }
+    }
 }
 
 #[allow(non_snake_case)]
@@ -130,9 +771,190 @@ pub struct PairwiseAligner {
 }
 
 impl PairwiseAligner {
+    /// Compute the alignment in the given mode. It doesn't need the GIL, so it's
+    /// called from `py.allow_threads` and can be used directly from Rust.
+    pub fn align(&mut self, x: &[u8], y: &[u8], mode: _AlignmentMode) -> PyResult<_Alignment> {
+        if self.scoring.long_gap.is_some() {
+            if mode == _AlignmentMode::Custom {
+                return Err(PyValueError::new_err(
+                    "Custom mode isn't supported with long gaps",
+                ));
+            }
+            return Ok(convex::align(&self.scoring, x, y, mode));
+        }
+        if let _AlignmentMode::Custom = mode {
+            return self.calculate_unwrapped_custom_alignment(x, y);
+        }
+
+        // Store the current clip penalties
+        let clip_penalties = [
+            self.scoring.xclip_prefix,
+            self.scoring.xclip_suffix,
+            self.scoring.yclip_prefix,
+            self.scoring.yclip_suffix,
+        ];
+
+        // Temporarily Over-write the clip penalties
+        let (xclip, yclip) = match mode {
+            _AlignmentMode::Global => (MIN_SCORE, MIN_SCORE),
+            _AlignmentMode::Semiglobal => (MIN_SCORE, 0),
+            _ => (0, 0),
+        };
+        self.scoring.xclip_prefix = xclip;
+        self.scoring.xclip_suffix = xclip;
+        self.scoring.yclip_prefix = yclip;
+        self.scoring.yclip_suffix = yclip;
+
+        // Compute the alignment
+        let alignment = self.calculate_unwrapped_custom_alignment(x, y);
+
+        // Set the clip penalties to the original values
+        self.scoring.xclip_prefix = clip_penalties[0];
+        self.scoring.xclip_suffix = clip_penalties[1];
+        self.scoring.yclip_prefix = clip_penalties[2];
+        self.scoring.yclip_suffix = clip_penalties[3];
+
+        let mut alignment = alignment?;
+        alignment.mode = mode;
+        if alignment.mode != _AlignmentMode::Global {
+            // Filter out Xclip and Yclip from alignment.operations
+            alignment.filter_clip_operations();
+        }
+        Ok(alignment)
+    }
+
+    /// Compute only the optimal score and the end of the alignment as `(score, xend, yend)`
+    /// using linear memory. Supports global, semiglobal and local modes, custom mode is
+    /// computed as global. Ties are resolved in favour of the smallest `yend`, then `xend`.
+    pub fn calculate_score(&self, x: &[u8], y: &[u8], mode: _AlignmentMode) -> (i32, usize, usize) {
+        if self.scoring.long_gap.is_some() {
+            let mode = match mode {
+                _AlignmentMode::Custom => _AlignmentMode::Global,
+                mode => mode,
+            };
+            let alignment = convex::align(&self.scoring, x, y, mode);
+            return (alignment.score, alignment.xend, alignment.yend);
+        }
+        let (local, free_y_ends) = match mode {
+            _AlignmentMode::Local => (true, true),
+            _AlignmentMode::Semiglobal => (false, true),
+            _ => (false, false),
+        };
+        let (m, n) = (x.len(), y.len());
+        let (ins_open, ins_extend) = (self.scoring.ins_gap_open, self.scoring.ins_gap_extend);
+        let (del_open, del_extend) = (self.scoring.del_gap_open, self.scoring.del_gap_extend);
+
+        // `prev_s`/`curr_s` keep the best scores of x[..i] aligned to y[..j] for the previous
+        // and current j, `d` and `ins` keep the best scores ending with a deletion/insertion
+        let mut prev_s: Vec<i32> = (0..=m)
+            .map(|i| if local { 0 } else { self.scoring.ins_gap(i) })
+            .collect();
+        let mut curr_s = vec![0; m + 1];
+        let mut d = vec![MIN_SCORE; m + 1];
+
+        let mut best = if local { (0, 0, 0) } else { (prev_s[m], m, 0) };
+
+        for j in 1..=n {
+            curr_s[0] = if free_y_ends {
+                0
+            } else {
+                self.scoring.del_gap(j)
+            };
+            let mut ins = MIN_SCORE;
+            for i in 1..=m {
+                d[i] = max(prev_s[i] + del_open + del_extend, d[i] + del_extend);
+                ins = max(curr_s[i - 1] + ins_open + ins_extend, ins + ins_extend);
+                let mut score = max(
+                    prev_s[i - 1] + (self.scoring.match_fn)(x[i - 1], y[j - 1]),
+                    max(d[i], ins),
+                );
+                if local {
+                    score = max(score, 0);
+                    if score > best.0 {
+                        best = (score, i, j);
+                    }
+                }
+                curr_s[i] = score;
+            }
+            if !local && free_y_ends && curr_s[m] > best.0 {
+                best = (curr_s[m], m, j);
+            }
+            std::mem::swap(&mut prev_s, &mut curr_s);
+        }
+
+        if !local && !free_y_ends {
+            best = (prev_s[m], m, n);
+        }
+        best
+    }
+
+    /// Compute the local alignment with the striped SIMD implementation. The score and the
+    /// end are found by the striped DP, the start by the same DP on reversed sequences,
+    /// and only the region between them is aligned with traceback.
+    pub fn align_local_simd(&mut self, x: &[u8], y: &[u8]) -> PyResult<_Alignment> {
+        let (score, xend, yend) = striped::local_score(&self.scoring, x, y)
+            .unwrap_or_else(|| self.calculate_score(x, y, _AlignmentMode::Local));
+        if score <= 0 {
+            return self.align(x, y, _AlignmentMode::Local);
+        }
+
+        let x_rev: Vec<u8> = x[..xend].iter().rev().copied().collect();
+        let y_rev: Vec<u8> = y[..yend].iter().rev().copied().collect();
+        let (_, x_rev_end, y_rev_end) = striped::local_score(&self.scoring, &x_rev, &y_rev)
+            .unwrap_or_else(|| self.calculate_score(&x_rev, &y_rev, _AlignmentMode::Local));
+        let (xstart, ystart) = (xend - x_rev_end, yend - y_rev_end);
+
+        let mut alignment =
+            self.align(&x[xstart..xend], &y[ystart..yend], _AlignmentMode::Global)?;
+        if alignment.score != score {
+            // The reversed pass found the start of another optimal alignment
+            return self.align(x, y, _AlignmentMode::Local);
+        }
+        alignment.xstart = xstart;
+        alignment.ystart = ystart;
+        alignment.xend = xend;
+        alignment.yend = yend;
+        alignment.xlen = x.len();
+        alignment.ylen = y.len();
+        alignment.mode = _AlignmentMode::Local;
+        Ok(alignment)
+    }
+
+    /// Align all pairs using up to `threads` threads. A single thread uses this aligner,
+    /// otherwise every thread gets its own aligner with the same scoring, so the buffers
+    /// are reused between pairs processed by the same thread.
+    pub fn align_pairs(
+        &mut self,
+        pairs: &[(&[u8], &[u8])],
+        mode: _AlignmentMode,
+        threads: usize,
+    ) -> PyResult<Vec<_Alignment>> {
+        if threads.min(pairs.len()) <= 1 {
+            return pairs.iter().map(|&(x, y)| self.align(x, y, mode)).collect();
+        }
+
+        let scoring = &self.scoring;
+        run_parallel_with(
+            pairs.len(),
+            threads,
+            || {
+                let capacity = DEFAULT_ALIGNER_CAPACITY;
+                PairwiseAligner::new(scoring.clone(), capacity, capacity)
+            },
+            |aligner, index| {
+                let (x, y) = pairs[index];
+                aligner.align(x, y, mode)
+            },
+        )
+        .into_iter()
+        .collect()
+    }
+
     fn calculate_unwrapped_custom_alignment(&mut self, x: &[u8], y: &[u8]) -> PyResult<_Alignment> {
         let (m, n) = (x.len(), y.len());
         self.traceback.init(m, n);
+        let (ins_open, ins_extend) = (self.scoring.ins_gap_open, self.scoring.ins_gap_extend);
+        let (del_open, del_extend) = (self.scoring.del_gap_open, self.scoring.del_gap_extend);
 
         // Set the initial conditions
         // We are repeating some work, but that's okay!
@@ -165,13 +987,12 @@ impl PairwiseAligner {
                 let mut tb = TracebackCell::new();
                 tb.set_all(TB_START);
                 if i == 1 {
-                    self.I[k][i] = self.scoring.gap_open + self.scoring.gap_extend;
+                    self.I[k][i] = ins_open + ins_extend;
                     tb.set_i_bits(TB_START);
                 } else {
                     // Insert all i characters
-                    let i_score = self.scoring.gap_open + self.scoring.gap_extend * (i as i32);
-                    let c_score =
-                        self.scoring.xclip_prefix + self.scoring.gap_open + self.scoring.gap_extend; // Clip then insert
+                    let i_score = ins_open + ins_extend * (i as i32);
+                    let c_score = self.scoring.xclip_prefix + ins_open + ins_extend; // Clip then insert
                     if i_score > c_score {
                         self.I[k][i] = i_score;
                         tb.set_i_bits(TB_INS);
@@ -224,13 +1045,12 @@ impl PairwiseAligner {
                 self.I[curr][0] = MIN_SCORE;
 
                 if j == 1 {
-                    self.D[curr][0] = self.scoring.gap_open + self.scoring.gap_extend;
+                    self.D[curr][0] = del_open + del_extend;
                     tb.set_d_bits(TB_START);
                 } else {
                     // Delete all j characters
-                    let d_score = self.scoring.gap_open + self.scoring.gap_extend * (j as i32);
-                    let c_score =
-                        self.scoring.yclip_prefix + self.scoring.gap_open + self.scoring.gap_extend;
+                    let d_score = del_open + del_extend * (j as i32);
+                    let c_score = self.scoring.yclip_prefix + del_open + del_extend;
                     if d_score > c_score {
                         self.D[curr][0] = d_score;
                         tb.set_d_bits(TB_DEL);
@@ -268,7 +1088,7 @@ impl PairwiseAligner {
             let xclip_score = self.scoring.xclip_prefix
                 + max(
                     self.scoring.yclip_prefix,
-                    self.scoring.gap_open + self.scoring.gap_extend * (j as i32),
+                    del_open + del_extend * (j as i32),
                 );
             for i in 1..m + 1 {
                 let p = x[i - 1];
@@ -276,8 +1096,8 @@ impl PairwiseAligner {
                 let match_fn = Arc::clone(&self.scoring.match_fn);
                 let m_score = self.S[prev][i - 1] + match_fn(p, q);
 
-                let i_score = self.I[curr][i - 1] + self.scoring.gap_extend;
-                let s_score = self.S[curr][i - 1] + self.scoring.gap_open + self.scoring.gap_extend;
+                let i_score = self.I[curr][i - 1] + ins_extend;
+                let s_score = self.S[curr][i - 1] + ins_open + ins_extend;
                 let best_i_score;
                 if i_score > s_score {
                     best_i_score = i_score;
@@ -287,8 +1107,8 @@ impl PairwiseAligner {
                     tb.set_i_bits(self.traceback.get(i - 1, j).get_s_bits());
                 }
 
-                let d_score = self.D[prev][i] + self.scoring.gap_extend;
-                let s_score = self.S[prev][i] + self.scoring.gap_open + self.scoring.gap_extend;
+                let d_score = self.D[prev][i] + del_extend;
+                let s_score = self.S[prev][i] + del_open + del_extend;
                 let best_d_score;
                 if d_score > s_score {
                     best_d_score = d_score;
@@ -321,9 +1141,7 @@ impl PairwiseAligner {
                     tb.set_s_bits(TB_XCLIP_PREFIX);
                 }
 
-                let yclip_score = self.scoring.yclip_prefix
-                    + self.scoring.gap_open
-                    + self.scoring.gap_extend * (i as i32);
+                let yclip_score = self.scoring.yclip_prefix + ins_open + ins_extend * (i as i32);
                 if yclip_score > best_s_score {
                     best_s_score = yclip_score;
                     tb.set_s_bits(TB_YCLIP_PREFIX);
@@ -369,7 +1187,7 @@ impl PairwiseAligner {
         for i in 1..=m {
             let j = n;
             let curr = j % 2;
-            let s_score = self.S[curr][i - 1] + self.scoring.gap_open + self.scoring.gap_extend;
+            let s_score = self.S[curr][i - 1] + ins_open + ins_extend;
             if s_score > self.I[curr][i] {
                 self.I[curr][i] = s_score;
                 let s_bit = self.traceback.get(i - 1, j).get_s_bits();
@@ -468,6 +1286,15 @@ impl PairwiseAligner {
 
 pub const DEFAULT_ALIGNER_CAPACITY: usize = 200;
 
+/// Resolve the number of threads to use, where `None` means all available CPUs.
+pub fn resolve_threads(threads: Option<usize>) -> PyResult<usize> {
+    match threads {
+        Some(0) => Err(PyValueError::new_err("threads must be positive")),
+        Some(threads) => Ok(threads),
+        None => Ok(thread::available_parallelism().map_or(1, |n| n.get())),
+    }
+}
+
 #[pymethods]
 impl PairwiseAligner {
     #[new]
@@ -485,99 +1312,109 @@ impl PairwiseAligner {
         }
     }
 
-    pub fn calculate_custom(&mut self, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
-        let unwrapped_alignment = self.calculate_unwrapped_custom_alignment(x, y)?;
-        Ok(Alignment(unwrapped_alignment))
+    pub fn calculate_custom(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align(x, y, _AlignmentMode::Custom))
+            .map(Alignment)
     }
 
-    pub fn calculate_global(&mut self, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
-        // Store the current clip penalties
-        let clip_penalties = [
-            self.scoring.xclip_prefix,
-            self.scoring.xclip_suffix,
-            self.scoring.yclip_prefix,
-            self.scoring.yclip_suffix,
-        ];
-
-        // Temporarily Over-write the clip penalties
-        self.scoring.xclip_prefix = MIN_SCORE;
-        self.scoring.xclip_suffix = MIN_SCORE;
-        self.scoring.yclip_prefix = MIN_SCORE;
-        self.scoring.yclip_suffix = MIN_SCORE;
-
-        // Compute the alignment
-        let mut alignment = self.calculate_unwrapped_custom_alignment(x, y)?;
-        alignment.mode = _AlignmentMode::Global;
-
-        // Set the clip penalties to the original values
-        self.scoring.xclip_prefix = clip_penalties[0];
-        self.scoring.xclip_suffix = clip_penalties[1];
-        self.scoring.yclip_prefix = clip_penalties[2];
-        self.scoring.yclip_suffix = clip_penalties[3];
+    pub fn calculate_global(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align(x, y, _AlignmentMode::Global))
+            .map(Alignment)
+    }
 
-        Ok(Alignment(alignment))
+    pub fn calculate_semiglobal(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align(x, y, _AlignmentMode::Semiglobal))
+            .map(Alignment)
     }
 
-    pub fn calculate_semiglobal(&mut self, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
-        // Store the current clip penalties
-        let clip_penalties = [
-            self.scoring.xclip_prefix,
-            self.scoring.xclip_suffix,
-            self.scoring.yclip_prefix,
-            self.scoring.yclip_suffix,
-        ];
+    pub fn calculate_local(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align(x, y, _AlignmentMode::Local))
+            .map(Alignment)
+    }
 
-        // Temporarily Over-write the clip penalties
-        self.scoring.xclip_prefix = MIN_SCORE;
-        self.scoring.xclip_suffix = MIN_SCORE;
-        self.scoring.yclip_prefix = 0;
-        self.scoring.yclip_suffix = 0;
+    /// Same as `calculate_local`, but uses the striped SIMD implementation (Farrar) which
+    /// is much faster for long sequences. The score is always the same, but another
+    /// alignment may be chosen among equally scored ones.
+    pub fn calculate_local_simd(&mut self, py: Python, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
+        py.allow_threads(|| self.align_local_simd(x, y))
+            .map(Alignment)
+    }
 
-        // Compute the alignment
-        let mut alignment = self.calculate_unwrapped_custom_alignment(x, y)?;
-        alignment.mode = _AlignmentMode::Semiglobal;
+    /// Extend the seed `x[x_seed:x_seed + seed_len]`, `y[y_seed:y_seed + seed_len]` in both
+    /// directions until the score drops more than `x_drop` below the best one.
+    #[pyo3(signature = (x, y, x_seed, y_seed, x_drop, seed_len=0))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_extension(
+        &self,
+        py: Python,
+        x: &[u8],
+        y: &[u8],
+        x_seed: usize,
+        y_seed: usize,
+        x_drop: i32,
+        seed_len: usize,
+    ) -> PyResult<Alignment> {
+        if x_seed + seed_len > x.len() || y_seed + seed_len > y.len() {
+            return Err(PyValueError::new_err("seed is out of the sequences"));
+        }
 
-        // Filter out Xclip and Yclip from alignment.operations
-        alignment.filter_clip_operations();
+        if x_drop < 0 {
+            return Err(PyValueError::new_err("x_drop can't be negative"));
+        }
 
-        // Set the clip penalties to the original values
-        self.scoring.xclip_prefix = clip_penalties[0];
-        self.scoring.xclip_suffix = clip_penalties[1];
-        self.scoring.yclip_prefix = clip_penalties[2];
-        self.scoring.yclip_suffix = clip_penalties[3];
+        if self.scoring.long_gap.is_some() {
+            return Err(PyValueError::new_err(
+                "X-drop extension isn't supported with long gaps",
+            ));
+        }
 
-        Ok(Alignment(alignment))
+        Ok(Alignment(py.allow_threads(|| {
+            xdrop::extend_seed(&self.scoring, x, y, x_seed, y_seed, seed_len, x_drop)
+        })))
     }
 
-    pub fn calculate_local(&mut self, x: &[u8], y: &[u8]) -> PyResult<Alignment> {
-        // Store the current clip penalties
-        let clip_penalties = [
-            self.scoring.xclip_prefix,
-            self.scoring.xclip_suffix,
-            self.scoring.yclip_prefix,
-            self.scoring.yclip_suffix,
-        ];
-
-        // Temporarily Over-write the clip penalties
-        self.scoring.xclip_prefix = 0;
-        self.scoring.xclip_suffix = 0;
-        self.scoring.yclip_prefix = 0;
-        self.scoring.yclip_suffix = 0;
+    /// Compute the global alignment score as `(score, x_end, y_end)` without traceback.
+    pub fn score_global(&self, py: Python, x: &[u8], y: &[u8]) -> (i32, usize, usize) {
+        py.allow_threads(|| self.calculate_score(x, y, _AlignmentMode::Global))
+    }
 
-        // Compute the alignment
-        let mut alignment = self.calculate_unwrapped_custom_alignment(x, y)?;
-        alignment.mode = _AlignmentMode::Local;
+    /// Compute the semiglobal alignment score as `(score, x_end, y_end)` without traceback.
+    pub fn score_semiglobal(&self, py: Python, x: &[u8], y: &[u8]) -> (i32, usize, usize) {
+        py.allow_threads(|| self.calculate_score(x, y, _AlignmentMode::Semiglobal))
+    }
 
-        // Filter out Xclip and Yclip from alignment.operations
-        alignment.filter_clip_operations();
+    /// Compute the local alignment score as `(score, x_end, y_end)` without traceback.
+    pub fn score_local(&self, py: Python, x: &[u8], y: &[u8]) -> (i32, usize, usize) {
+        py.allow_threads(|| self.calculate_score(x, y, _AlignmentMode::Local))
+    }
 
-        // Set the clip penalties to the original values
-        self.scoring.xclip_prefix = clip_penalties[0];
-        self.scoring.xclip_suffix = clip_penalties[1];
-        self.scoring.yclip_prefix = clip_penalties[2];
-        self.scoring.yclip_suffix = clip_penalties[3];
+    /// Align every `(x, y)` pair in one call. `threads` defaults to the number of CPUs.
+    #[pyo3(signature = (pairs, mode="global", threads=None))]
+    pub fn align_many(
+        &mut self,
+        py: Python,
+        pairs: Vec<(&[u8], &[u8])>,
+        mode: &str,
+        threads: Option<usize>,
+    ) -> PyResult<Vec<Alignment>> {
+        let mode = parse_alignment_mode(mode)?;
+        let threads = resolve_threads(threads)?;
+        let alignments = py.allow_threads(|| self.align_pairs(&pairs, mode, threads))?;
+        Ok(alignments.into_iter().map(Alignment).collect())
+    }
 
-        Ok(Alignment(alignment))
+    /// Align `query` as `x` against every target as `y` in one call.
+    #[pyo3(signature = (query, targets, mode="global", threads=None))]
+    pub fn align_one_to_many(
+        &mut self,
+        py: Python,
+        query: &[u8],
+        targets: Vec<&[u8]>,
+        mode: &str,
+        threads: Option<usize>,
+    ) -> PyResult<Vec<Alignment>> {
+        let pairs: Vec<(&[u8], &[u8])> = targets.into_iter().map(|y| (query, y)).collect();
+        self.align_many(py, pairs, mode, threads)
     }
 }
 
@@ -0,0 +1,242 @@
+#[rustfmt::skip]
+use bio::alignment::sparse::{
+    expand_kmer_matches         as _expand_kmer_matches,
+    find_kmer_matches           as _find_kmer_matches,
+    lcskpp                      as _lcskpp,
+    sdpkpp                      as _sdpkpp,
+    sdpkpp_union_lcskpp_path    as _sdpkpp_union_lcskpp_path,
+    SparseAlignmentResult       as _SparseAlignmentResult,
+};
+#[rustfmt::skip]
+use bio_types::alignment::{
+    Alignment           as _Alignment,
+    AlignmentMode       as _AlignmentMode,
+    AlignmentOperation  as _AlignmentOperation,
+};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::alignment::alignment_type::Alignment;
+use crate::alignment::scoring::{PairwiseAligner, Scoring};
+
+#[pyclass]
+struct SparseAlignmentResult(_SparseAlignmentResult);
+
+#[pymethods]
+impl SparseAlignmentResult {
+    #[getter]
+    pub fn path(&self) -> Vec<usize> {
+        self.0.path.clone()
+    }
+
+    #[getter]
+    pub fn score(&self) -> u32 {
+        self.0.score
+    }
+
+    #[getter]
+    pub fn dp_vector(&self) -> Vec<(u32, i32)> {
+        self.0.dp_vector.clone()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "<SparseAlignmentResult: score={}, path={:?}>",
+            self.0.score, self.0.path
+        )
+    }
+}
+
+fn check_k(k: usize) -> PyResult<()> {
+    if k == 0 {
+        Err(PyValueError::new_err("k must be positive"))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_matches(matches: &[(u32, u32)]) -> PyResult<()> {
+    if matches.windows(2).any(|w| w[0] >= w[1]) {
+        Err(PyValueError::new_err(
+            "matches must be sorted and must not contain duplicates",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[pyfunction]
+fn find_kmer_matches(x: &[u8], y: &[u8], k: usize) -> PyResult<Vec<(u32, u32)>> {
+    check_k(k)?;
+    Ok(_find_kmer_matches(x, y, k))
+}
+
+#[pyfunction]
+fn expand_kmer_matches(
+    x: &[u8],
+    y: &[u8],
+    k: usize,
+    matches: Vec<(u32, u32)>,
+    allowed_mismatches: usize,
+) -> PyResult<Vec<(u32, u32)>> {
+    check_k(k)?;
+    check_matches(&matches)?;
+    if matches
+        .iter()
+        .any(|&(i, j)| i as usize + k > x.len() || j as usize + k > y.len())
+    {
+        return Err(PyValueError::new_err("matches are out of the sequences"));
+    }
+    Ok(_expand_kmer_matches(x, y, k, &matches, allowed_mismatches))
+}
+
+#[pyfunction]
+fn lcskpp(matches: Vec<(u32, u32)>, k: usize) -> PyResult<SparseAlignmentResult> {
+    check_k(k)?;
+    check_matches(&matches)?;
+    Ok(SparseAlignmentResult(_lcskpp(&matches, k)))
+}
+
+#[pyfunction]
+fn sdpkpp(
+    matches: Vec<(u32, u32)>,
+    k: usize,
+    match_score: u32,
+    gap_open: i32,
+    gap_extend: i32,
+) -> PyResult<SparseAlignmentResult> {
+    check_k(k)?;
+    check_matches(&matches)?;
+    if gap_open > 0 {
+        return Err(PyValueError::new_err("gap_open can't be positive"));
+    }
+    if gap_extend > 0 {
+        return Err(PyValueError::new_err("gap_extend can't be positive"));
+    }
+    Ok(SparseAlignmentResult(_sdpkpp(
+        &matches,
+        k,
+        match_score,
+        gap_open,
+        gap_extend,
+    )))
+}
+
+#[pyfunction]
+fn sdpkpp_union_lcskpp_path(
+    matches: Vec<(u32, u32)>,
+    k: usize,
+    match_score: u32,
+    gap_open: i32,
+    gap_extend: i32,
+) -> PyResult<Vec<usize>> {
+    check_k(k)?;
+    check_matches(&matches)?;
+    if gap_open > 0 {
+        return Err(PyValueError::new_err("gap_open can't be positive"));
+    }
+    if gap_extend > 0 {
+        return Err(PyValueError::new_err("gap_extend can't be positive"));
+    }
+    Ok(_sdpkpp_union_lcskpp_path(
+        &matches,
+        k,
+        match_score,
+        gap_open,
+        gap_extend,
+    ))
+}
+
+/// Expand the chain of k-mer matches into the full alignment. Matched k-mers are put
+/// on the diagonal, and the regions between them are aligned globally with `scoring`.
+#[pyfunction]
+fn chain_to_alignment(
+    x: &[u8],
+    y: &[u8],
+    matches: Vec<(u32, u32)>,
+    path: Vec<usize>,
+    k: usize,
+    scoring: Scoring,
+) -> PyResult<Alignment> {
+    check_k(k)?;
+    if path.is_empty() {
+        return Err(PyValueError::new_err("path can't be empty"));
+    }
+
+    let mut anchors: Vec<(usize, usize)> = Vec::with_capacity(path.len());
+    for &index in path.iter() {
+        let &(i, j) = matches
+            .get(index)
+            .ok_or_else(|| PyValueError::new_err("path refers to a non-existent match"))?;
+        let (i, j) = (i as usize, j as usize);
+        if i + k > x.len() || j + k > y.len() {
+            return Err(PyValueError::new_err("matches are out of the sequences"));
+        }
+        if let Some(&(prev_i, prev_j)) = anchors.last() {
+            if i < prev_i || j < prev_j {
+                return Err(PyValueError::new_err(
+                    "path must be ordered along both sequences",
+                ));
+            }
+        }
+        anchors.push((i, j));
+    }
+
+    let match_fn = &scoring.match_fn;
+    let mut aligner = PairwiseAligner::new(scoring.clone(), k, k);
+    let mut operations: Vec<_AlignmentOperation> = Vec::new();
+    let mut score = 0;
+    let (xstart, ystart) = anchors[0];
+    let (mut i, mut j) = anchors[0];
+
+    for &(anchor_i, anchor_j) in anchors.iter() {
+        // Skip the part of the k-mer that overlaps already aligned bases
+        let overlap = (i.saturating_sub(anchor_i)).max(j.saturating_sub(anchor_j));
+        if overlap >= k {
+            continue;
+        }
+        let (anchor_i, anchor_j) = (anchor_i + overlap, anchor_j + overlap);
+
+        if anchor_i > i || anchor_j > j {
+            let gap = aligner.align(&x[i..anchor_i], &y[j..anchor_j], _AlignmentMode::Global)?;
+            score += gap.score;
+            operations.extend(gap.operations);
+        }
+
+        for offset in 0..k - overlap {
+            let (a, b) = (x[anchor_i + offset], y[anchor_j + offset]);
+            score += match_fn(a, b);
+            operations.push(if a == b {
+                _AlignmentOperation::Match
+            } else {
+                _AlignmentOperation::Subst
+            });
+        }
+        i = anchor_i + k - overlap;
+        j = anchor_j + k - overlap;
+    }
+
+    Ok(Alignment(_Alignment {
+        score,
+        xstart,
+        ystart,
+        xend: i,
+        yend: j,
+        xlen: x.len(),
+        ylen: y.len(),
+        operations,
+        mode: _AlignmentMode::Local,
+    }))
+}
+
+#[pymodule]
+pub fn sparse(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<SparseAlignmentResult>()?;
+    m.add_function(wrap_pyfunction!(find_kmer_matches, m)?)?;
+    m.add_function(wrap_pyfunction!(expand_kmer_matches, m)?)?;
+    m.add_function(wrap_pyfunction!(lcskpp, m)?)?;
+    m.add_function(wrap_pyfunction!(sdpkpp, m)?)?;
+    m.add_function(wrap_pyfunction!(sdpkpp_union_lcskpp_path, m)?)?;
+    m.add_function(wrap_pyfunction!(chain_to_alignment, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,227 @@
+// Striped Smith-Waterman by Farrar (https://doi.org/10.1093/bioinformatics/btl582),
+// with the lazy-F loop and overflow handling following the SSW library
+// (https://github.com/mengyao/Complete-Striped-Smith-Waterman-Library).
+//
+// Scores are kept in unsigned saturating lanes: every profile score is shifted by
+// `bias` so that it's not negative, and local alignment scores are never below zero
+// anyway. Computation starts with 16 8-bit lanes and moves to 8 16-bit lanes if the
+// scores don't fit.
+
+use wide::{u16x8, u8x16};
+
+use crate::alignment::scoring::Scoring;
+
+trait Lanes: Copy {
+    const LANES: usize;
+    const MAX: i32;
+
+    fn splat(value: i32) -> Self;
+    fn from_values(values: &[i32]) -> Self;
+    fn adds(self, rhs: Self) -> Self;
+    fn subs(self, rhs: Self) -> Self;
+    fn max(self, rhs: Self) -> Self;
+    /// Whether any lane of `self` is greater than the same lane of `rhs`.
+    fn any_gt(self, rhs: Self) -> bool;
+    /// Move every lane one position up, filling the first lane with zero.
+    fn shift(self) -> Self;
+    fn lane(self, k: usize) -> i32;
+    fn horizontal_max(self) -> i32;
+}
+
+macro_rules! impl_lanes {
+    ($simd:ident, $elem:ty, $lanes:expr) => {
+        impl Lanes for $simd {
+            const LANES: usize = $lanes;
+            const MAX: i32 = <$elem>::MAX as i32;
+
+            #[inline(always)]
+            fn splat(value: i32) -> Self {
+                $simd::splat(value as $elem)
+            }
+
+            fn from_values(values: &[i32]) -> Self {
+                let mut array = [0 as $elem; $lanes];
+                for (lane, &value) in array.iter_mut().zip(values.iter()) {
+                    *lane = value as $elem;
+                }
+                $simd::new(array)
+            }
+
+            #[inline(always)]
+            fn adds(self, rhs: Self) -> Self {
+                self.saturating_add(rhs)
+            }
+
+            #[inline(always)]
+            fn subs(self, rhs: Self) -> Self {
+                self.saturating_sub(rhs)
+            }
+
+            #[inline(always)]
+            fn max(self, rhs: Self) -> Self {
+                $simd::max(self, rhs)
+            }
+
+            #[inline(always)]
+            fn any_gt(self, rhs: Self) -> bool {
+                self.saturating_sub(rhs) != $simd::splat(0)
+            }
+
+            #[inline(always)]
+            fn shift(self) -> Self {
+                let array = self.to_array();
+                let mut shifted = [0 as $elem; $lanes];
+                shifted[1..].copy_from_slice(&array[..$lanes - 1]);
+                $simd::new(shifted)
+            }
+
+            #[inline(always)]
+            fn lane(self, k: usize) -> i32 {
+                self.to_array()[k] as i32
+            }
+
+            fn horizontal_max(self) -> i32 {
+                self.to_array().into_iter().max().unwrap_or(0) as i32
+            }
+        }
+    };
+}
+
+impl_lanes!(u8x16, u8, 16);
+impl_lanes!(u16x8, u16, 8);
+
+/// Compute the local alignment score with `V` lanes. Returns `None` if the scores
+/// don't fit into the lanes.
+fn striped_local_score<V: Lanes>(
+    scoring: &Scoring,
+    x: &[u8],
+    y: &[u8],
+) -> Option<(i32, usize, usize)> {
+    let (m, n) = (x.len(), y.len());
+    if m == 0 || n == 0 {
+        return Some((0, 0, 0));
+    }
+
+    // Gap of length `l` costs `gap_open + gap_extend * l`. Deletions (E) are carried
+    // between the columns, insertions (F) go along x inside of a column
+    let del_open = -(scoring.del_gap_open + scoring.del_gap_extend);
+    let del_extend = -scoring.del_gap_extend;
+    let ins_open = -(scoring.ins_gap_open + scoring.ins_gap_extend);
+    let ins_extend = -scoring.ins_gap_extend;
+    if [del_open, del_extend, ins_open, ins_extend]
+        .iter()
+        .any(|&penalty| penalty > V::MAX)
+    {
+        return None;
+    }
+
+    // Build the striped query profile for every symbol of y: lane k of segment s
+    // keeps the score of x[s + k * seg_len], padding keeps zero
+    let seg_len = m.div_ceil(V::LANES);
+    let mut symbols = [usize::MAX; 256];
+    let mut scores: Vec<Vec<i32>> = Vec::new();
+    for &b in y {
+        if symbols[b as usize] == usize::MAX {
+            symbols[b as usize] = scores.len();
+            scores.push(x.iter().map(|&a| (scoring.match_fn)(a, b)).collect());
+        }
+    }
+    let bias = -scores.iter().flatten().copied().min().unwrap_or(0).min(0);
+    let top = scores.iter().flatten().copied().max().unwrap_or(0);
+    if bias > V::MAX || top + bias > V::MAX {
+        return None;
+    }
+    let profile: Vec<Vec<V>> = scores
+        .iter()
+        .map(|row| {
+            (0..seg_len)
+                .map(|s| {
+                    let values: Vec<i32> = (0..V::LANES)
+                        .map(|k| row.get(s + k * seg_len).map_or(0, |score| score + bias))
+                        .collect();
+                    V::from_values(&values)
+                })
+                .collect()
+        })
+        .collect();
+
+    let v_zero = V::splat(0);
+    let v_bias = V::splat(bias);
+    let v_del_open = V::splat(del_open);
+    let v_del_extend = V::splat(del_extend);
+    let v_ins_open = V::splat(ins_open);
+    let v_ins_extend = V::splat(ins_extend);
+    let mut h_load = vec![v_zero; seg_len];
+    let mut h_store = vec![v_zero; seg_len];
+    let mut e = vec![v_zero; seg_len];
+
+    let mut best = (0, 0, 0);
+    let mut best_column = vec![v_zero; seg_len];
+
+    for (j, &b) in y.iter().enumerate() {
+        let column_profile = &profile[symbols[b as usize]];
+        let mut v_f = v_zero;
+        let mut v_max_column = v_zero;
+        let mut v_h = h_store[seg_len - 1].shift();
+        std::mem::swap(&mut h_load, &mut h_store);
+
+        for s in 0..seg_len {
+            v_h = v_h.adds(column_profile[s]).subs(v_bias);
+            v_h = v_h.max(e[s]).max(v_f);
+            v_max_column = v_max_column.max(v_h);
+            h_store[s] = v_h;
+
+            e[s] = e[s].subs(v_del_extend).max(v_h.subs(v_del_open));
+            v_f = v_f.subs(v_ins_extend).max(v_h.subs(v_ins_open));
+            v_h = h_load[s];
+        }
+
+        // Lazy-F loop: propagate insertions crossing segment boundaries
+        'lazy_f: for _ in 0..V::LANES {
+            v_f = v_f.shift();
+            for s in 0..seg_len {
+                let v_h_old = h_store[s];
+                let v_h = v_h_old.max(v_f);
+                v_max_column = v_max_column.max(v_h);
+                h_store[s] = v_h;
+
+                e[s] = e[s].max(v_h.subs(v_del_open));
+                v_f = v_f.subs(v_ins_extend);
+                // The main loop already opened insertions from the H before the update,
+                // so F only has to go on while it beats them
+                if !v_f.any_gt(v_h_old.subs(v_ins_open)) {
+                    break 'lazy_f;
+                }
+            }
+        }
+
+        let column_max = v_max_column.horizontal_max();
+        if column_max > best.0 {
+            if column_max + bias >= V::MAX {
+                return None;
+            }
+            best = (column_max, 0, j + 1);
+            best_column.copy_from_slice(&h_store);
+        }
+    }
+
+    // Find the first x position reaching the best score in the best column
+    if best.0 > 0 {
+        best.1 = (0..m)
+            .find(|&i| best_column[i % seg_len].lane(i / seg_len) == best.0)
+            .map_or(0, |i| i + 1);
+    }
+    Some(best)
+}
+
+/// Compute the local alignment score and its end as `(score, xend, yend)`. Returns `None`
+/// if the scores don't fit into 16-bit lanes or the scoring has long gaps, so the scalar
+/// implementation should be used.
+/// Ties are resolved in favour of the smallest `yend`, then `xend`.
+pub fn local_score(scoring: &Scoring, x: &[u8], y: &[u8]) -> Option<(i32, usize, usize)> {
+    if scoring.long_gap.is_some() {
+        return None;
+    }
+    striped_local_score::<u8x16>(scoring, x, y)
+        .or_else(|| striped_local_score::<u16x8>(scoring, x, y))
+}
@@ -0,0 +1,259 @@
+// Seed extension with X-drop termination (Zhang et al., https://doi.org/10.1089/10665270050081478):
+// the affine gap DP is computed row by row, cells scoring more than `x_drop` below the best
+// score seen so far are dropped, and every row only spans the columns still reachable from
+// live cells of the previous row.
+
+#[rustfmt::skip]
+use bio_types::alignment::{
+    Alignment           as _Alignment,
+    AlignmentMode       as _AlignmentMode,
+    AlignmentOperation  as _AlignmentOperation,
+};
+use std::cmp::max;
+
+use crate::alignment::scoring::{Scoring, MIN_SCORE};
+
+// Traceback bits: the lowest two bits keep the source of the best score, the others
+// keep whether the deletion and insertion gaps are extended or opened in this cell
+const TB_DIAG: u8 = 0;
+const TB_DEL: u8 = 1;
+const TB_INS: u8 = 2;
+const TB_SOURCE: u8 = 0b11;
+const TB_DEL_EXTEND: u8 = 0b100;
+const TB_INS_EXTEND: u8 = 0b1000;
+
+/// Extend the alignment from the start of both sequences to the right. Returns the best
+/// score, the lengths of `x` and `y` covered by the extension and its operations.
+/// `open_gap` is a deletion or insertion already opened before the start, which the
+/// extension continues without paying the gap open penalty again.
+fn extend(
+    scoring: &Scoring,
+    x: &[u8],
+    y: &[u8],
+    x_drop: i32,
+    open_gap: Option<_AlignmentOperation>,
+) -> (i32, usize, usize, Vec<_AlignmentOperation>) {
+    let (m, n) = (x.len(), y.len());
+    let del_open = scoring.del_gap_open + scoring.del_gap_extend;
+    let del_extend = scoring.del_gap_extend;
+    let ins_open = scoring.ins_gap_open + scoring.ins_gap_extend;
+    let ins_extend = scoring.ins_gap_extend;
+    let mut best = (0, 0, 0);
+    let threshold = |best: i32| max(best - x_drop, MIN_SCORE / 2);
+
+    // Traceback of the computed part of every row as (first column, cells)
+    let mut rows: Vec<(usize, Vec<u8>)> = Vec::new();
+
+    let (del_opened, ins_opened) = match open_gap {
+        Some(_AlignmentOperation::Del) => (true, false),
+        Some(_AlignmentOperation::Ins) => (false, true),
+        _ => (false, false),
+    };
+
+    // The first row only contains the leading deletion
+    let mut prev_h = vec![0];
+    let mut prev_f = vec![if ins_opened { 0 } else { MIN_SCORE }];
+    let mut tb_row = vec![TB_DIAG];
+    for j in 1..=n {
+        let score = if del_opened {
+            del_extend * j as i32
+        } else {
+            del_open + del_extend * (j as i32 - 1)
+        };
+        if score < threshold(best.0) {
+            break;
+        }
+        prev_h.push(score);
+        prev_f.push(MIN_SCORE);
+        tb_row.push(
+            TB_DEL
+                | if j > 1 || del_opened {
+                    TB_DEL_EXTEND
+                } else {
+                    0
+                },
+        );
+    }
+    rows.push((0, tb_row));
+    let mut prev_lo = 0;
+
+    for i in 1..=m {
+        let prev_hi = prev_lo + prev_h.len();
+        let prev = |row: &[i32], j: usize| {
+            if j >= prev_lo && j < prev_hi {
+                row[j - prev_lo]
+            } else {
+                MIN_SCORE
+            }
+        };
+
+        let mut h_row: Vec<i32> = Vec::with_capacity(prev_h.len() + 1);
+        let mut f_row: Vec<i32> = Vec::with_capacity(prev_h.len() + 1);
+        let mut tb_row: Vec<u8> = Vec::with_capacity(prev_h.len() + 1);
+        let mut e = MIN_SCORE;
+        let mut j = prev_lo;
+        while j <= n {
+            let mut tb = 0;
+
+            let h_left = h_row.last().copied().unwrap_or(MIN_SCORE);
+            if e + del_extend > h_left + del_open {
+                e += del_extend;
+                tb |= TB_DEL_EXTEND;
+            } else {
+                e = h_left + del_open;
+            }
+
+            let mut f = prev(&prev_f, j) + ins_extend;
+            if f > prev(&prev_h, j) + ins_open {
+                tb |= TB_INS_EXTEND;
+            } else {
+                f = prev(&prev_h, j) + ins_open;
+            }
+
+            let mut h = MIN_SCORE;
+            if j > 0 {
+                h = prev(&prev_h, j - 1) + (scoring.match_fn)(x[i - 1], y[j - 1]);
+            }
+            if e > h {
+                h = e;
+                tb |= TB_DEL;
+            }
+            if f > h {
+                h = f;
+                tb = (tb & !TB_SOURCE) | TB_INS;
+            }
+
+            let dropped = h < threshold(best.0);
+            if dropped {
+                h = MIN_SCORE;
+                e = MIN_SCORE;
+                f = MIN_SCORE;
+            } else if h > best.0 {
+                best = (h, i, j);
+            }
+            h_row.push(h);
+            f_row.push(f);
+            tb_row.push(tb);
+
+            // Only a deletion can continue the row beyond the previous one
+            if dropped && j >= prev_hi {
+                break;
+            }
+            j += 1;
+        }
+
+        let (first, last) = match (
+            h_row.iter().position(|&h| h != MIN_SCORE),
+            h_row.iter().rposition(|&h| h != MIN_SCORE),
+        ) {
+            (Some(first), Some(last)) => (first, last),
+            _ => break,
+        };
+        prev_h = h_row[first..=last].to_vec();
+        prev_f = f_row[first..=last].to_vec();
+        prev_lo += first;
+        rows.push((prev_lo, tb_row[first..=last].to_vec()));
+    }
+
+    let tb = |i: usize, j: usize| {
+        let (lo, row) = &rows[i];
+        row[j - lo]
+    };
+    let (mut i, mut j) = (best.1, best.2);
+    let mut source = TB_DIAG;
+    let mut operations = Vec::with_capacity(i + j);
+    while i > 0 || j > 0 {
+        let cell = tb(i, j);
+        if source == TB_DIAG {
+            source = cell & TB_SOURCE;
+        }
+        match source {
+            TB_DEL => {
+                operations.push(_AlignmentOperation::Del);
+                if cell & TB_DEL_EXTEND == 0 {
+                    source = TB_DIAG;
+                }
+                j -= 1;
+            }
+            TB_INS => {
+                operations.push(_AlignmentOperation::Ins);
+                if cell & TB_INS_EXTEND == 0 {
+                    source = TB_DIAG;
+                }
+                i -= 1;
+            }
+            _ => {
+                operations.push(if x[i - 1] == y[j - 1] {
+                    _AlignmentOperation::Match
+                } else {
+                    _AlignmentOperation::Subst
+                });
+                i -= 1;
+                j -= 1;
+            }
+        }
+    }
+    operations.reverse();
+
+    (best.0, best.1, best.2, operations)
+}
+
+/// Align the seed `x[x_seed..x_seed + seed_len]` against `y[y_seed..y_seed + seed_len]`
+/// without gaps and extend it in both directions until the score drops by more than
+/// `x_drop` below the best one.
+pub fn extend_seed(
+    scoring: &Scoring,
+    x: &[u8],
+    y: &[u8],
+    x_seed: usize,
+    y_seed: usize,
+    seed_len: usize,
+    x_drop: i32,
+) -> _Alignment {
+    let x_rev: Vec<u8> = x[..x_seed].iter().rev().copied().collect();
+    let y_rev: Vec<u8> = y[..y_seed].iter().rev().copied().collect();
+    let (left_score, left_x, left_y, left_operations) =
+        extend(scoring, &x_rev, &y_rev, x_drop, None);
+
+    // Without a seed, a gap ending the left extension continues into the right one
+    let open_gap = match left_operations.first() {
+        Some(&operation) if seed_len == 0 => Some(operation),
+        _ => None,
+    };
+    let (x_seed_end, y_seed_end) = (x_seed + seed_len, y_seed + seed_len);
+    let (right_score, right_x, right_y, right_operations) = extend(
+        scoring,
+        &x[x_seed_end..],
+        &y[y_seed_end..],
+        x_drop,
+        open_gap,
+    );
+
+    let mut score = left_score + right_score;
+    let mut operations = left_operations;
+    operations.reverse();
+    for (&a, &b) in x[x_seed..x_seed_end]
+        .iter()
+        .zip(y[y_seed..y_seed_end].iter())
+    {
+        score += (scoring.match_fn)(a, b);
+        operations.push(if a == b {
+            _AlignmentOperation::Match
+        } else {
+            _AlignmentOperation::Subst
+        });
+    }
+    operations.extend(right_operations);
+
+    _Alignment {
+        score,
+        xstart: x_seed - left_x,
+        ystart: y_seed - left_y,
+        xend: x_seed_end + right_x,
+        yend: y_seed_end + right_y,
+        xlen: x.len(),
+        ylen: y.len(),
+        operations,
+        mode: _AlignmentMode::Local,
+    }
+}
@@ -24,14 +24,18 @@ use bio::alphabets::{
     Alphabet        as _Alphabet,
     RankTransform   as _RankTransform,
 };
+use pyo3::basic::CompareOp;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::{IntoPyDict, PyBytes, PyDict};
+use pyo3::types::{IntoPyDict, PyBytes, PyDict, PyType};
 use pyo3::wrap_pymodule;
 use std::collections::HashMap;
 
-#[pyclass]
-struct Alphabet(_Alphabet);
+use crate::utils::{hash, richcmp};
+
+#[derive(Clone)]
+#[pyclass(module = "bioforma.alphabets")]
+pub struct Alphabet(pub _Alphabet);
 
 #[pymethods]
 impl Alphabet {
@@ -70,6 +74,26 @@ impl Alphabet {
         self.0.len()
     }
 
+    fn __reduce__<'p>(slf: &'p PyCell<Self>, py: Python<'p>) -> (&'p PyType, (&'p PyBytes,)) {
+        (slf.get_type(), (slf.borrow().symbols(py),))
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(&self.0)
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
+        richcmp(&self.0, &other.0, op)
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        self.clone()
+    }
+
     pub fn __and__(&self, other: &Alphabet) -> Self {
         Alphabet(self.0.intersection(&other.0))
     }
@@ -79,7 +103,8 @@ impl Alphabet {
     }
 }
 
-#[pyclass]
+#[derive(Clone)]
+#[pyclass(module = "bioforma.alphabets")]
 struct RankTransform(_RankTransform);
 
 #[pymethods]
@@ -125,6 +150,29 @@ impl RankTransform {
         res.into_py_dict(py)
     }
 
+    /// Ranks are given to the symbols in ascending order, so the transform is restored
+    /// from the alphabet of its symbols.
+    fn __reduce__(slf: &PyCell<Self>) -> (&PyType, (Alphabet,)) {
+        let symbols: Vec<u8> = slf.borrow().0.ranks.keys().map(|key| key as u8).collect();
+        (slf.get_type(), (Alphabet(_Alphabet::new(&symbols)),))
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(&self.0)
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
+        richcmp(&self.0, &other.0, op)
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        self.clone()
+    }
+
     pub fn __repr__(&self) -> String {
         let a: Vec<String> = self
             .0
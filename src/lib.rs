@@ -5,6 +5,7 @@ use pyo3::wrap_pymodule;
 mod alignment;
 mod alphabets;
 mod seq_analysis;
+mod utils;
 
 pub fn get_version() -> String {
     let version = env!("CARGO_PKG_VERSION").to_string();
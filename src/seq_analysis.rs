@@ -8,12 +8,17 @@ use bio::seq_analysis::orf::{
     Finder      as _Finder,
     Orf         as _Orf,
 };
+use pyo3::basic::CompareOp;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyType};
 use pyo3::wrap_pymodule;
 use std::array::TryFromSliceError;
 
+use crate::utils::{hash, richcmp};
+
+mod barcodes;
+
 #[pyfunction]
 fn gc_content(sequence: &[u8]) -> f32 {
     _gc_content(sequence)
@@ -63,16 +68,22 @@ impl Finder {
         }
     }
 
-    pub fn find_all(&self, sequence: &[u8]) -> Vec<Orf> {
-        self.0.find_all(sequence).map(Orf).collect()
+    pub fn find_all(&self, py: Python, sequence: &[u8]) -> Vec<Orf> {
+        py.allow_threads(|| self.0.find_all(sequence).map(Orf).collect())
     }
 }
 
-#[pyclass]
+#[derive(Clone)]
+#[pyclass(module = "bioforma.seq_analysis.orf")]
 struct Orf(_Orf);
 
 #[pymethods]
 impl Orf {
+    #[new]
+    pub fn new(start: usize, end: usize, offset: i8) -> Self {
+        Orf(_Orf { start, end, offset })
+    }
+
     #[getter]
     pub fn start(&self) -> usize {
         self.0.start
@@ -88,6 +99,27 @@ impl Orf {
         self.0.offset
     }
 
+    fn __reduce__(slf: &PyCell<Self>) -> (&PyType, (usize, usize, i8)) {
+        let orf = slf.borrow().0;
+        (slf.get_type(), (orf.start, orf.end, orf.offset))
+    }
+
+    fn __hash__(&self) -> u64 {
+        hash(&self.0)
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> PyResult<bool> {
+        richcmp(&self.0, &other.0, op)
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        self.clone()
+    }
+
     pub fn __repr__(&self) -> String {
         format!(
             "<Orf: start={}, end={}, offset={}>",
@@ -114,12 +146,14 @@ fn gc(_py: Python, m: &PyModule) -> PyResult<()> {
 pub fn seq_analysis(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_wrapped(wrap_pymodule!(gc))?;
     m.add_wrapped(wrap_pymodule!(orf))?;
+    m.add_wrapped(wrap_pymodule!(barcodes::barcodes))?;
 
     let sys = PyModule::import(py, "sys")?;
     let sys_modules: &PyDict = sys.getattr("modules")?.downcast()?;
 
     sys_modules.set_item("bioforma.seq_analysis.gc", m.getattr("gc")?)?;
     sys_modules.set_item("bioforma.seq_analysis.orf", m.getattr("orf")?)?;
+    sys_modules.set_item("bioforma.seq_analysis.barcodes", m.getattr("barcodes")?)?;
 
     Ok(())
 }
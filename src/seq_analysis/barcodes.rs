@@ -0,0 +1,353 @@
+// Barcode correction against a whitelist and UMI clustering in the way of UMI-tools.
+// Whitelist barcodes are split into `max_dist + 1` segments: a barcode within `max_dist`
+// edits keeps at least one of them unchanged, shifted by at most `max_dist` positions,
+// so only whitelist barcodes sharing a segment with it have to be compared.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::collections::{HashMap, HashSet};
+
+use crate::alignment::distance::Metric;
+use crate::alignment::scoring::resolve_threads;
+use crate::utils::run_parallel;
+
+/// Whitelist barcodes by their symbols from `start` to `end`.
+struct Segment {
+    start: usize,
+    end: usize,
+    barcodes: HashMap<Vec<u8>, Vec<usize>>,
+}
+
+#[pyclass]
+pub struct BarcodeCorrector {
+    whitelist: Vec<Vec<u8>>,
+    exact: HashMap<Vec<u8>, usize>,
+    segments: Vec<Segment>,
+    metric: Metric,
+    max_dist: u32,
+}
+
+impl BarcodeCorrector {
+    /// Return the index of the closest whitelist barcode, or `None` when there's no barcode
+    /// within `max_dist` or several of them are equally close.
+    fn closest(&self, barcode: &[u8]) -> Option<usize> {
+        if let Some(&index) = self.exact.get(barcode) {
+            return Some(index);
+        }
+        let shift = match self.metric {
+            Metric::Hamming if barcode.len() != self.whitelist[0].len() => return None,
+            Metric::Hamming => 0,
+            Metric::Levenshtein => self.max_dist as isize,
+        };
+
+        let mut candidates: Vec<usize> = Vec::new();
+        for segment in self.segments.iter() {
+            for offset in -shift..=shift {
+                let start = segment.start as isize + offset;
+                let end = segment.end as isize + offset;
+                if start < 0 || end > barcode.len() as isize {
+                    continue;
+                }
+                if let Some(indices) = segment.barcodes.get(&barcode[start as usize..end as usize])
+                {
+                    candidates.extend(indices.iter().copied());
+                }
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut best: Option<(u32, usize)> = None;
+        let mut ambiguous = false;
+        for index in candidates {
+            let distance =
+                self.metric
+                    .distance(barcode, &self.whitelist[index], Some(self.max_dist));
+            if distance > self.max_dist {
+                continue;
+            }
+            match best {
+                Some((best_distance, _)) if distance > best_distance => {}
+                Some((best_distance, _)) if distance == best_distance => ambiguous = true,
+                _ => {
+                    best = Some((distance, index));
+                    ambiguous = false;
+                }
+            }
+        }
+        match best {
+            Some((_, index)) if !ambiguous => Some(index),
+            _ => None,
+        }
+    }
+}
+
+#[pymethods]
+impl BarcodeCorrector {
+    #[new]
+    #[pyo3(signature = (whitelist, metric="hamming", max_dist=1))]
+    pub fn new(whitelist: Vec<Vec<u8>>, metric: &str, max_dist: u32) -> PyResult<Self> {
+        let metric = Metric::parse(metric)?;
+        if whitelist.is_empty() {
+            return Err(PyValueError::new_err("whitelist can't be empty"));
+        }
+        let length = whitelist[0].len();
+        if whitelist.iter().any(|barcode| barcode.len() != length) {
+            return Err(PyValueError::new_err(
+                "All whitelist barcodes must have the same length",
+            ));
+        }
+        // Every segment must keep at least one symbol
+        if max_dist as usize >= length {
+            return Err(PyValueError::new_err(
+                "max_dist must be less than the barcode length",
+            ));
+        }
+
+        let mut unique = HashSet::new();
+        let whitelist: Vec<Vec<u8>> = whitelist
+            .into_iter()
+            .filter(|barcode| unique.insert(barcode.clone()))
+            .collect();
+        let exact = whitelist
+            .iter()
+            .enumerate()
+            .map(|(index, barcode)| (barcode.clone(), index))
+            .collect();
+        let parts = max_dist as usize + 1;
+        let segments = (0..parts)
+            .map(|part| {
+                let (start, end) = (part * length / parts, (part + 1) * length / parts);
+                let mut barcodes: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+                for (i, barcode) in whitelist.iter().enumerate() {
+                    barcodes
+                        .entry(barcode[start..end].to_vec())
+                        .or_default()
+                        .push(i);
+                }
+                Segment {
+                    start,
+                    end,
+                    barcodes,
+                }
+            })
+            .collect();
+
+        Ok(BarcodeCorrector {
+            whitelist,
+            exact,
+            segments,
+            metric,
+            max_dist,
+        })
+    }
+
+    #[getter]
+    pub fn metric(&self) -> &'static str {
+        self.metric.name()
+    }
+
+    #[getter]
+    pub fn max_dist(&self) -> u32 {
+        self.max_dist
+    }
+
+    /// Return the closest whitelist barcode within `max_dist`, or `None` when there's no
+    /// such barcode or the closest one is ambiguous.
+    pub fn correct<'p>(&self, py: Python<'p>, barcode: &[u8]) -> Option<&'p PyBytes> {
+        let index = py.allow_threads(|| self.closest(barcode))?;
+        Some(PyBytes::new(py, &self.whitelist[index]))
+    }
+
+    /// Correct every barcode, see `correct`.
+    #[pyo3(signature = (barcodes, threads=None))]
+    pub fn correct_many<'p>(
+        &self,
+        py: Python<'p>,
+        barcodes: Vec<Vec<u8>>,
+        threads: Option<usize>,
+    ) -> PyResult<Vec<Option<&'p PyBytes>>> {
+        let threads = resolve_threads(threads)?;
+        let indices = py.allow_threads(|| {
+            run_parallel(barcodes.len(), threads, |i| self.closest(&barcodes[i]))
+        });
+        Ok(indices
+            .into_iter()
+            .map(|index| index.map(|index| PyBytes::new(py, &self.whitelist[index])))
+            .collect())
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.whitelist.len()
+    }
+
+    pub fn __contains__(&self, barcode: &[u8]) -> bool {
+        self.exact.contains_key(barcode)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "<BarcodeCorrector: barcodes={}, metric={}, max_dist={}>",
+            self.whitelist.len(),
+            self.metric.name(),
+            self.max_dist
+        )
+    }
+}
+
+#[derive(Clone, Copy)]
+enum UmiMethod {
+    Adjacency,
+    Directional,
+}
+
+/// Group UMIs sorted by decreasing counts, given the neighbours of every UMI.
+fn group_umis(method: UmiMethod, counts: &[u64], neighbours: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut groups = Vec::new();
+    let mut grouped = vec![false; counts.len()];
+    match method {
+        // Every UMI absorbs the UMIs reachable through neighbours with at most half of its
+        // count, then the next most abundant UMI that isn't absorbed starts a new group.
+        UmiMethod::Directional => {
+            for lead in 0..counts.len() {
+                if grouped[lead] {
+                    continue;
+                }
+                grouped[lead] = true;
+                let mut group = vec![lead];
+                let mut k = 0;
+                while k < group.len() {
+                    let node = group[k];
+                    for &neighbour in neighbours[node].iter() {
+                        if !grouped[neighbour] && counts[node] + 1 >= 2 * counts[neighbour] {
+                            grouped[neighbour] = true;
+                            group.push(neighbour);
+                        }
+                    }
+                    k += 1;
+                }
+                groups.push(group);
+            }
+        }
+        // The most abundant UMIs of every connected component are taken as leads until
+        // their neighbours cover the whole component, and every lead groups its neighbours
+        // that aren't leads or grouped by a more abundant lead.
+        UmiMethod::Adjacency => {
+            let mut visited = vec![false; counts.len()];
+            for start in 0..counts.len() {
+                if visited[start] {
+                    continue;
+                }
+                visited[start] = true;
+                let mut component = vec![start];
+                let mut k = 0;
+                while k < component.len() {
+                    for &neighbour in neighbours[component[k]].iter() {
+                        if !visited[neighbour] {
+                            visited[neighbour] = true;
+                            component.push(neighbour);
+                        }
+                    }
+                    k += 1;
+                }
+                component.sort_unstable();
+
+                let mut covered: HashSet<usize> = HashSet::new();
+                let mut leads = Vec::new();
+                for &lead in component.iter() {
+                    if covered.len() == component.len() {
+                        break;
+                    }
+                    covered.insert(lead);
+                    covered.extend(neighbours[lead].iter().copied());
+                    leads.push(lead);
+                }
+                for &lead in leads.iter() {
+                    grouped[lead] = true;
+                }
+                for lead in leads {
+                    let mut group = vec![lead];
+                    for &neighbour in neighbours[lead].iter() {
+                        if !grouped[neighbour] {
+                            grouped[neighbour] = true;
+                            group.push(neighbour);
+                        }
+                    }
+                    groups.push(group);
+                }
+            }
+        }
+    }
+    groups
+}
+
+/// Cluster UMIs with their counts in the way of UMI-tools. UMIs within `max_dist` are
+/// neighbours, and the `adjacency` or `directional` method groups them around the most
+/// abundant ones. Every group starts with its lead UMI.
+#[pyfunction]
+#[pyo3(signature = (counts, method="directional", metric="hamming", max_dist=1, threads=None))]
+fn cluster_umis<'p>(
+    py: Python<'p>,
+    counts: HashMap<Vec<u8>, u64>,
+    method: &str,
+    metric: &str,
+    max_dist: u32,
+    threads: Option<usize>,
+) -> PyResult<Vec<Vec<&'p PyBytes>>> {
+    let method = match method {
+        "adjacency" => UmiMethod::Adjacency,
+        "directional" => UmiMethod::Directional,
+        _ => {
+            return Err(PyValueError::new_err(
+                "method must be 'adjacency' or 'directional'",
+            ))
+        }
+    };
+    let metric = Metric::parse(metric)?;
+    let threads = resolve_threads(threads)?;
+
+    // The most abundant UMIs go first, ties are ordered by the UMIs themselves
+    let mut umis: Vec<(Vec<u8>, u64)> = counts.into_iter().collect();
+    umis.sort_unstable_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    let (umis, counts): (Vec<Vec<u8>>, Vec<u64>) = umis.into_iter().unzip();
+    if let Some(first) = umis.first() {
+        metric.check(first, &umis)?;
+    }
+
+    let groups = py.allow_threads(|| {
+        let close = run_parallel(umis.len(), threads, |i| {
+            (i + 1..umis.len())
+                .filter(|&j| metric.distance(&umis[i], &umis[j], Some(max_dist)) <= max_dist)
+                .collect::<Vec<usize>>()
+        });
+        let mut neighbours = vec![Vec::new(); umis.len()];
+        for (i, close) in close.into_iter().enumerate() {
+            for j in close {
+                neighbours[i].push(j);
+                neighbours[j].push(i);
+            }
+        }
+        for umi_neighbours in neighbours.iter_mut() {
+            umi_neighbours.sort_unstable();
+        }
+        group_umis(method, &counts, &neighbours)
+    });
+
+    Ok(groups
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|index| PyBytes::new(py, &umis[index]))
+                .collect()
+        })
+        .collect())
+}
+
+#[pymodule]
+pub fn barcodes(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<BarcodeCorrector>()?;
+    m.add_function(wrap_pyfunction!(cluster_umis, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,81 @@
+// Helpers shared by the python classes of all modules.
+
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::PyNotImplementedError;
+use pyo3::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Hash the value for `__hash__`.
+pub fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Compare the values for `__richcmp__`, where only equality is supported.
+pub fn richcmp<T: PartialEq>(slf: T, other: T, op: CompareOp) -> PyResult<bool> {
+    match op {
+        CompareOp::Eq => Ok(slf == other),
+        CompareOp::Ne => Ok(slf != other),
+        _ => Err(PyNotImplementedError::new_err(
+            "Operation isn't supported for this type",
+        )),
+    }
+}
+
+/// Run the task for every index from 0 to `count` in `threads` threads and return the
+/// results in the order of the indices.
+pub fn run_parallel<T, F>(count: usize, threads: usize, task: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    run_parallel_with(count, threads, || (), |_, index| task(index))
+}
+
+/// Same as `run_parallel`, but every thread creates its own state with `init` and passes
+/// it to the task, so buffers can be reused between the indices processed by the thread.
+pub fn run_parallel_with<S, T, I, F>(count: usize, threads: usize, init: I, task: F) -> Vec<T>
+where
+    T: Send,
+    I: Fn() -> S + Sync,
+    F: Fn(&mut S, usize) -> T + Sync,
+{
+    let threads = threads.min(count);
+    if threads <= 1 {
+        let mut state = init();
+        return (0..count).map(|index| task(&mut state, index)).collect();
+    }
+
+    let next_index = AtomicUsize::new(0);
+    let worker = || {
+        let mut state = init();
+        let mut results = Vec::new();
+        loop {
+            let index = next_index.fetch_add(1, Ordering::Relaxed);
+            if index >= count {
+                return results;
+            }
+            results.push((index, task(&mut state, index)));
+        }
+    };
+    let results = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|_| scope.spawn(worker)).collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| match handle.join() {
+                Ok(results) => results,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut ordered: Vec<Option<T>> = (0..count).map(|_| None).collect();
+    for (index, result) in results {
+        ordered[index] = Some(result);
+    }
+    ordered.into_iter().flatten().collect()
+}
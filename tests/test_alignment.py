@@ -1,4 +1,9 @@
+from concurrent.futures import ThreadPoolExecutor
+import copy
 from itertools import repeat
+import pickle
+import random
+import threading
 
 from bioforma.alignment import (
     Alignment,
@@ -11,7 +16,14 @@ from bioforma.alignment import (
     Yclip,
     Scoring,
     PairwiseAligner,
+    BandedAligner,
     DEFAULT_ALIGNER_CAPACITY,
+    MIN_SCORE,
+    MATCH,
+    SUBST,
+    DEL,
+    INS,
+    parse_cigar,
 )
 from bioforma.alignment.distance import (
     hamming,
@@ -19,6 +31,10 @@ from bioforma.alignment.distance import (
     levenshtein,
     simd_levenshtein,
     simd_bounded_levenshtein,
+    levenshtein_alignment,
+    damerau_levenshtein,
+    pairwise_matrix,
+    one_to_many,
 )
 
 
@@ -50,6 +66,62 @@ def test_levenshtein():
     assert levenshtein(b'AAA', b'TTTT') == 4
 
 
+def test_levenshtein_alignment():
+    x = b'ACCGTGGAT'
+    y = b'AAAAACCGTTGAT'
+    alignment = levenshtein_alignment(x, y)
+    assert alignment.score == -levenshtein(x, y)
+    assert alignment.mode == 'global'
+    assert alignment.cigar(False) == '4D5=1X3='
+    assert levenshtein_alignment(b'', b'AC').operations == [Del(), Del()]
+    assert levenshtein_alignment(b'ACGT', b'AGCT').operations == [Match(), Subst(), Subst(), Match()]
+
+
+def test_damerau_levenshtein():
+    assert damerau_levenshtein(b'ACGT', b'AGCT') == 1
+    assert levenshtein(b'ACGT', b'AGCT') == 2
+    assert damerau_levenshtein(b'CA', b'ABC') == 2
+    assert damerau_levenshtein(b'', b'ACG') == 3
+    assert damerau_levenshtein(b'ACCGTGGAT', b'AAAAACCGTTGAT') == 5
+
+
+def test_pairwise_matrix():
+    seqs = [b'ACGT', b'ACGA', b'TTTT', b'ACG']
+    # condensed upper triangle: (0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)
+    condensed = pairwise_matrix(seqs, threads=3)
+    assert condensed.shape == (6,)
+    assert condensed.format == 'I'
+    assert condensed.tolist() == [1, 3, 1, 4, 1, 4]
+    assert condensed[5] == simd_levenshtein(seqs[2], seqs[3])
+    assert pairwise_matrix(seqs[:3], metric='hamming').tolist() == [1, 3, 4]
+    assert pairwise_matrix(seqs[:1]).tolist() == []
+    i, j, d = pairwise_matrix(seqs, max_dist=2, threads=2)
+    assert (i.format, j.format, d.format) == ('I', 'I', 'I')
+    assert list(zip(i.tolist(), j.tolist(), d.tolist())) == [(0, 1, 1), (0, 3, 1), (1, 3, 1)]
+    i, j, d = pairwise_matrix(seqs, max_dist=0)
+    assert i.tolist() == j.tolist() == d.tolist() == []
+    for args, kwargs in (([],), {}), ((seqs,), {'metric': 'hamming'}), ((seqs,), {'metric': 'cosine'}):
+        try:
+            pairwise_matrix(*args, **kwargs)
+        except ValueError:
+            assert True
+        else:
+            assert False
+
+
+def test_one_to_many():
+    seqs = [b'ACGT', b'ACGA', b'TTTT', b'ACG']
+    assert one_to_many(b'ACGT', seqs).tolist() == [0, 1, 3, 1]
+    assert one_to_many(b'ACGT', seqs, max_dist=1, threads=2).tolist() == [0, 1, 2, 1]
+    assert one_to_many(b'ACGT', seqs[:3], metric='hamming').tolist() == [0, 1, 3]
+    try:
+        one_to_many(b'ACGT', seqs, metric='hamming')
+    except ValueError:
+        assert True
+    else:
+        assert False
+
+
 def test_simd_levenshtein():
     x = b'ACCGTGGAT'
     y = b'AAAAACCGTTGAT'
@@ -149,6 +221,25 @@ def test_alignment_cigar():
     assert alignment.cigar(False) == "1X1=1X"
 
 
+def test_alignment_cigar_global_and_local():
+    x = b"ACCGTGGAT"
+    y = b"AAAAACCGTTGAT"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    aligner = PairwiseAligner(scoring)
+
+    alignment = aligner.calculate_global(x, y)
+    assert alignment.cigar() == "4D5=1X3="
+    assert alignment.cigar(extended=False) == "4D9M"
+
+    alignment = aligner.calculate_local(b"TT" + x + b"TT", y)
+    assert alignment.cigar() == "2S5=1X3=2S"
+    assert alignment.cigar(True, extended=False) == "2H9M2H"
+
+    parsed = Alignment.from_cigar(alignment.cigar(), 0, alignment.y_start, alignment.x_len, alignment.y_len)
+    assert parsed.operations == alignment.operations
+    assert (parsed.x_start, parsed.x_end, parsed.y_end) == (alignment.x_start, alignment.x_end, alignment.y_end)
+
+
 def test_alignment_pretty():
     alignment = Alignment(
         score=1,
@@ -647,3 +738,1048 @@ def test_aligner_new_pairwise_aligner():  # test default capacity
         Match(),
         Match(),
     ]
+
+
+def test_banded_aligner_init():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    try:
+        BandedAligner(scoring, k=0, w=10)
+    except ValueError:
+        assert True
+    else:
+        assert False
+
+
+def test_banded_aligner_same_as_pairwise_aligner():
+    x = b"AGCACACGTGTGCGCTATACAGTAAGTAGTAGTACACGTGTCACAGTTGTACTAGCATGAC"
+    y = b"AGCACACGTGTGCGCTATACAGTACACGTGTCACAGTTGTACTAGCATGAC"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    aligner = PairwiseAligner(scoring, m=len(x), n=len(y))
+    banded_aligner = BandedAligner(scoring, k=8, w=6, m=len(x), n=len(y))
+
+    for mode in ('global', 'semiglobal', 'local'):
+        alignment = getattr(aligner, f'calculate_{mode}')(x, y)
+        banded_alignment = getattr(banded_aligner, f'calculate_{mode}')(x, y)
+        assert banded_alignment.mode == mode
+        assert banded_alignment.score == alignment.score
+        assert banded_alignment.x_start == alignment.x_start
+        assert banded_alignment.y_start == alignment.y_start
+        assert banded_alignment.operations == alignment.operations
+
+
+def test_banded_local_aligner():
+    x = b"ACCGTGGAT"
+    y = b"AAAAACCGTTGAT"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    aligner = BandedAligner(scoring, k=3, w=3)
+    alignment = aligner.calculate_local(x, y)
+    assert alignment.x_start == 0
+    assert alignment.y_start == 4
+    assert alignment.operations == [
+        Match(),
+        Match(),
+        Match(),
+        Match(),
+        Match(),
+        Subst(),
+        Match(),
+        Match(),
+        Match(),
+    ]
+
+
+def test_banded_custom_aligner():
+    x = b"ACCGTGGAT"
+    y = b"AAAAACCGTTGAT"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    alignment = BandedAligner(scoring, k=3, w=3).calculate_custom(x, y)
+    assert alignment.mode == 'custom'
+    assert alignment.score == PairwiseAligner(scoring).calculate_custom(x, y).score
+
+
+
+def test_banded_aligner_short_sequences():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    aligner = PairwiseAligner(scoring)
+    banded_aligner = BandedAligner(scoring, k=3, w=3)
+    # Sequences without k-mers are aligned without the band
+    for x, y in ((b"", b""), (b"", b"ACGT"), (b"ACGT", b""), (b"AC", b"ACGT"), (b"ACGT", b"CG")):
+        for mode in ('custom', 'global', 'semiglobal', 'local'):
+            alignment = getattr(aligner, f'calculate_{mode}')(x, y)
+            banded_alignment = getattr(banded_aligner, f'calculate_{mode}')(x, y)
+            assert banded_alignment.mode == mode
+            assert banded_alignment.score == alignment.score, (x, y, mode)
+            assert banded_alignment.operations == alignment.operations
+    assert banded_aligner.calculate_global(b"", b"ACGT").score == -9
+
+
+NUC_4_4_EXCERPT = """\
+#
+# This matrix is an excerpt of NUC.4.4
+#
+   A  T  G  C  N
+A  5 -4 -4 -4 -2
+T -4  5 -4 -4 -2
+G -4 -4  5 -4 -2
+C -4 -4 -4  5 -2
+N -2 -2 -2 -2 -1
+"""
+
+
+def test_scoring_gap_extend_validation():
+    try:
+        Scoring(-5, 1, 'blosum62')
+    except ValueError as e:
+        assert "gap_extend can't be positive" in str(e)
+    else:
+        assert False
+    try:
+        Scoring.from_scores(-5, 1, match_score=1, mismatch_score=-1)
+    except ValueError as e:
+        assert "gap_extend can't be positive" in str(e)
+    else:
+        assert False
+
+
+def test_scoring_from_matrix():
+    transition, transversion = -1, -3
+    matrix = {
+        'A': {'A': 2, 'G': transition, 'C': transversion, 'T': transversion},
+        'G': {'A': transition, 'G': 2, 'C': transversion, 'T': transversion},
+        'C': {'A': transversion, 'G': transversion, 'C': 2, 'T': transition},
+        'T': {'A': transversion, 'G': transversion, 'C': transition, 'T': 2},
+    }
+    scoring = Scoring.from_matrix(-5, -1, matrix)
+    aligner = PairwiseAligner(scoring)
+    assert aligner.calculate_global(b"ACGT", b"ACGT").score == 8
+    assert aligner.calculate_global(b"ACGT", b"GCGT").score == 6 + transition
+    assert aligner.calculate_global(b"ACGT", b"CCGT").score == 6 + transversion
+    # Lowercase symbols are scored like uppercase ones
+    assert aligner.calculate_global(b"acgt", b"cCGT").score == 6 + transversion
+    # Symbols out of the matrix get the lowest score of the matrix
+    assert aligner.calculate_global(b"ACGT", b"NCGT").score == 6 + transversion
+    scoring = Scoring.from_matrix(-5, -1, matrix, default=0)
+    assert PairwiseAligner(scoring).calculate_global(b"ACGT", b"NCGT").score == 6
+
+
+def test_scoring_from_matrix_list():
+    scoring = Scoring.from_matrix_list(-5, -1, b"ACGTN", [
+        [5, -4, -4, -4, -2],
+        [-4, 5, -4, -4, -2],
+        [-4, -4, 5, -4, -2],
+        [-4, -4, -4, 5, -2],
+        [-2, -2, -2, -2, -1],
+    ])
+    aligner = PairwiseAligner(scoring)
+    alignment = aligner.calculate_global(b"ACGTN", b"ACGTA")
+    assert alignment.score == 18
+    assert alignment.operations == [Match(), Match(), Match(), Match(), Subst()]
+
+    try:
+        Scoring.from_matrix_list(-5, -1, b"AC", [[1, -1], [-1]])
+    except ValueError as e:
+        assert "must be 2x2" in str(e)
+    else:
+        assert False
+    try:
+        Scoring.from_matrix_list(-5, -1, b"AA", [[1, -1], [-1, 1]])
+    except ValueError as e:
+        assert "duplicate" in str(e)
+    else:
+        assert False
+
+
+def test_scoring_from_matrix_file(tmp_path):
+    path = tmp_path / "NUC.4.4"
+    path.write_text(NUC_4_4_EXCERPT)
+    scoring = Scoring.from_matrix_file(-5, -1, path)
+    aligner = PairwiseAligner(scoring)
+    assert aligner.calculate_global(b"ACGTN", b"ACGTA").score == 18
+    assert aligner.calculate_global(b"ACGTN", b"ACGTN").score == 19
+
+    path.write_text(NUC_4_4_EXCERPT.replace("N -2", "X -2"))
+    try:
+        Scoring.from_matrix_file(-5, -1, path)
+    except ValueError as e:
+        assert "same symbols" in str(e)
+    else:
+        assert False
+    try:
+        Scoring.from_matrix_file(-5, -1, tmp_path / "missing")
+    except FileNotFoundError:
+        assert True
+    else:
+        assert False
+
+
+def test_scoring_from_function():
+    def bisulfite_match(a, b):
+        # Unmethylated C reads as T after bisulfite conversion
+        if a == b or (a, b) == (ord('T'), ord('C')):
+            return 1
+        return -1
+
+    scoring = Scoring.from_function(-5, -1, bisulfite_match)
+    aligner = PairwiseAligner(scoring)
+    assert aligner.calculate_global(b"ATTG", b"ACTG").score == 4
+    assert aligner.calculate_global(b"ACTG", b"ATTG").score == 2
+
+
+def test_scoring_from_function_errors():
+    try:
+        Scoring.from_function(-5, -1, 1)
+    except TypeError as e:
+        assert "callable" in str(e)
+    else:
+        assert False
+    try:
+        Scoring.from_function(-5, -1, lambda a, b: 1 // 0)
+    except ZeroDivisionError:
+        assert True
+    else:
+        assert False
+    try:
+        Scoring.from_function(-5, -1, lambda a, b: "1")
+    except TypeError:
+        assert True
+    else:
+        assert False
+
+
+def test_scoring_getters():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    assert scoring.gap_open == -5
+    assert scoring.gap_extend == -1
+    assert scoring.match_scores == (1, -1)
+    assert scoring.xclip_prefix == MIN_SCORE
+    assert scoring.yclip_suffix == MIN_SCORE
+    assert Scoring(-5, -1, 'blosum62').match_scores is None
+    assert repr(scoring) == (
+        f"<Scoring: gap_open=-5, gap_extend=-1, match_scores=(1, -1), xclip_prefix={MIN_SCORE}, "
+        f"xclip_suffix={MIN_SCORE}, yclip_prefix={MIN_SCORE}, yclip_suffix={MIN_SCORE}>"
+    )
+
+
+def test_scoring_clip_builders():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    clipped = scoring.xclip(-5).yclip(0)
+    assert (clipped.xclip_prefix, clipped.xclip_suffix) == (-5, -5)
+    assert (clipped.yclip_prefix, clipped.yclip_suffix) == (0, 0)
+    assert scoring.xclip_prefix == MIN_SCORE
+
+    clipped = clipped.with_clip(xclip_suffix=MIN_SCORE, yclip_prefix=-3)
+    assert (clipped.xclip_prefix, clipped.xclip_suffix) == (-5, MIN_SCORE)
+    assert (clipped.yclip_prefix, clipped.yclip_suffix) == (-3, 0)
+
+    try:
+        scoring.yclip(1)
+    except ValueError as e:
+        assert "yclip_prefix can't be positive" in str(e)
+    else:
+        assert False
+    try:
+        scoring.with_clip(xclip_prefix=MIN_SCORE - 1)
+    except ValueError as e:
+        assert "xclip_prefix can't be less than MIN_SCORE" in str(e)
+    else:
+        assert False
+
+
+def test_custom_clip_pairwise_aligner():
+    x = b"GGGGGGACGTACGTACGT"
+    y = b"AAAAACGTACGTACGTAAAA"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1).xclip(-5).yclip(0)
+    alignment = PairwiseAligner(scoring).calculate_custom(x, y)
+    assert alignment.mode == 'custom'
+    assert alignment.score == 7
+    assert alignment.x_start == 6
+    assert alignment.y_start == 4
+    assert alignment.operations == [Yclip(4), Xclip(6), *repeat(Match(), 12), Yclip(4)]
+
+
+def test_pairwise_aligner_in_threads():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    pairs = [(b"ACCGTGGAT" * i, b"AAAAACCGTTGAT" * i) for i in range(1, 30)]
+    expected = [PairwiseAligner(scoring).calculate_local(x, y) for x, y in pairs]
+    local = threading.local()
+
+    def align(pair):
+        # Aligners keep mutable buffers, so every thread needs its own one
+        if not hasattr(local, "aligner"):
+            local.aligner = PairwiseAligner(scoring)
+        return local.aligner.calculate_local(*pair)
+
+    with ThreadPoolExecutor(max_workers=4) as executor:
+        alignments = list(executor.map(align, pairs))
+    assert [a.score for a in alignments] == [a.score for a in expected]
+    assert [a.operations for a in alignments] == [a.operations for a in expected]
+
+
+def test_pairwise_aligner_align_many():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    pairs = [(b"ACCGTGGAT" * i, b"AAAAACCGTTGAT" * (30 - i)) for i in range(1, 30)]
+    aligner = PairwiseAligner(scoring)
+    for mode in ('global', 'semiglobal', 'local', 'custom'):
+        calculate = getattr(PairwiseAligner(scoring), f'calculate_{mode}')
+        expected = [calculate(x, y) for x, y in pairs]
+        for threads in (1, 4):
+            alignments = aligner.align_many(pairs, mode=mode, threads=threads)
+            assert [a.mode for a in alignments] == [mode] * len(pairs)
+            assert [a.score for a in alignments] == [a.score for a in expected]
+            assert [a.operations for a in alignments] == [a.operations for a in expected]
+    assert aligner.align_many([]) == []
+    try:
+        aligner.align_many(pairs, threads=0)
+    except ValueError as e:
+        assert "threads must be positive" in str(e)
+    else:
+        assert False
+    try:
+        aligner.align_many(pairs, mode='banded')
+    except ValueError as e:
+        assert "can't be used as the mode" in str(e)
+    else:
+        assert False
+
+
+def test_pairwise_aligner_align_one_to_many():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    query = b"ACCGTGGAT"
+    targets = [b"AAAAACCGTTGAT", b"ACCGTGGAT", b"TTTT", b""]
+    aligner = PairwiseAligner(scoring)
+    alignments = aligner.align_one_to_many(query, targets, mode='local')
+    expected = [PairwiseAligner(scoring).calculate_local(query, y) for y in targets]
+    assert [a.score for a in alignments] == [a.score for a in expected]
+    assert alignments[1].score == len(query)
+
+
+def test_pairwise_aligner_score_only():
+    x = b"ACCGTGGAT"
+    y = b"AAAAACCGTTGAT"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    aligner = PairwiseAligner(scoring)
+    assert aligner.score_local(x, y) == (7, 9, 13)
+    assert aligner.score_semiglobal(x, y) == (7, 9, 13)
+    assert aligner.score_global(x, y) == (aligner.calculate_global(x, y).score, 9, 13)
+    assert aligner.score_global(b"", b"AC") == (-7, 0, 2)
+    assert aligner.score_local(b"", b"AC") == (0, 0, 0)
+
+
+def test_pairwise_aligner_score_only_matches_alignment():
+    rng = random.Random(42)
+    scorings = [
+        Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1),
+        Scoring.from_scores(-1, -1, match_score=2, mismatch_score=-3),
+        Scoring.from_scores(0, -2, match_score=1, mismatch_score=-1),
+    ]
+    for scoring in scorings:
+        aligner = PairwiseAligner(scoring)
+        for _ in range(50):
+            x = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(0, 30)))
+            y = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(0, 30)))
+            for mode in ('global', 'semiglobal', 'local'):
+                alignment = getattr(aligner, f'calculate_{mode}')(x, y)
+                score, x_end, y_end = getattr(aligner, f'score_{mode}')(x, y)
+                assert score == alignment.score, (mode, x, y)
+                if mode != 'local':
+                    assert x_end == len(x)
+
+
+def test_pairwise_aligner_local_simd():
+    x = b"ACCGTGGAT"
+    y = b"AAAAACCGTTGAT"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    alignment = PairwiseAligner(scoring).calculate_local_simd(x, y)
+    assert alignment.mode == 'local'
+    assert alignment.score == 7
+    assert (alignment.x_start, alignment.x_end) == (0, 9)
+    assert (alignment.y_start, alignment.y_end) == (4, 13)
+    assert (alignment.x_len, alignment.y_len) == (9, 13)
+    assert alignment.operations == [*repeat(Match(), 5), Subst(), *repeat(Match(), 3)]
+
+
+def test_pairwise_aligner_local_simd_matches_scalar():
+    rng = random.Random(7)
+    scorings = [
+        Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1),
+        Scoring.from_scores(-1, -1, match_score=2, mismatch_score=-3),
+        Scoring.from_scores(0, -2, match_score=1, mismatch_score=-1),
+        # Scores exceeding 8-bit and 16-bit lanes
+        Scoring.from_scores(-5, -1, match_score=9, mismatch_score=-4),
+        Scoring.from_scores(-500, -100, match_score=3000, mismatch_score=-3000),
+        Scoring(-10, -1, 'blosum62'),
+    ]
+    for scoring in scorings:
+        aligner = PairwiseAligner(scoring)
+        for length in (0, 1, 5, 17, 40, 100):
+            for _ in range(5):
+                x = bytes(rng.choice(b"ACGT") for _ in range(length))
+                y = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(0, 2 * length + 2)))
+                if rng.random() < 0.5 and len(y) > 10:
+                    y = y[:5] + x + y[5:]
+                expected = aligner.calculate_local(x, y)
+                alignment = aligner.calculate_local_simd(x, y)
+                assert alignment.score == expected.score, (x, y)
+                assert alignment.x_end - alignment.x_start == sum(
+                    op in (Match(), Subst(), Ins()) for op in alignment.operations
+                )
+                assert alignment.y_end - alignment.y_start == sum(
+                    op in (Match(), Subst(), Del()) for op in alignment.operations
+                )
+                if alignment.score > 0:
+                    assert aligner.score_local(x, y)[1:] == (alignment.x_end, alignment.y_end)
+
+
+def test_pairwise_aligner_local_simd_without_gap_open():
+    scoring = Scoring.from_scores(0, -1, match_score=4, mismatch_score=-4)
+    aligner = PairwiseAligner(scoring)
+    x = b"CGCACTAGGGCAAATGTTAGGTCAAG"
+    y = b"TCTATAGCTCCTGGAT"
+    assert aligner.calculate_local_simd(x, y).score == aligner.calculate_local(x, y).score
+
+    rng = random.Random(5)
+    for _ in range(1000):
+        x = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(1, 40)))
+        y = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(1, 40)))
+        assert aligner.calculate_local_simd(x, y).score == aligner.calculate_local(x, y).score, (x, y)
+
+
+def test_pairwise_aligner_extension():
+    seed = b"GATTACA"
+    x = b"TTTTTTTT" + b"CCGTA" + seed + b"ACGTTGCATG" + b"AAAAAAAA"
+    y = b"GGGG" + b"CCGTA" + seed + b"ACGTGCATG" + b"CCCCCCCCCC"
+    scoring = Scoring.from_scores(-2, -1, match_score=1, mismatch_score=-1)
+    aligner = PairwiseAligner(scoring)
+    alignment = aligner.calculate_extension(x, y, x_seed=13, y_seed=9, seed_len=len(seed), x_drop=3)
+    assert alignment.mode == 'local'
+    assert (alignment.x_start, alignment.x_end) == (8, 30)
+    assert (alignment.y_start, alignment.y_end) == (4, 25)
+    assert (alignment.x_len, alignment.y_len) == (len(x), len(y))
+    assert alignment.score == 5 + 7 + 4 - 3 + 5
+    assert alignment.operations == [*repeat(Match(), 15), Ins(), *repeat(Match(), 6)]
+
+    # The gap costs more than x_drop allows to lose, so the extension stops before it
+    alignment = aligner.calculate_extension(x, y, x_seed=13, y_seed=9, seed_len=len(seed), x_drop=2)
+    assert (alignment.x_end, alignment.y_end) == (24, 20)
+    assert alignment.score == 16
+
+
+def test_pairwise_aligner_extension_without_drop():
+    rng = random.Random(3)
+    scoring = Scoring.from_scores(-3, -1, match_score=2, mismatch_score=-3)
+    aligner = PairwiseAligner(scoring)
+    for _ in range(30):
+        x = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(0, 10)))
+        y = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(0, 10)))
+        alignment = aligner.calculate_extension(x, y, 0, 0, x_drop=1000)
+        best = max(
+            aligner.score_global(x[:i], y[:j])[0]
+            for i in range(len(x) + 1)
+            for j in range(len(y) + 1)
+        )
+        assert alignment.score == best
+        assert (alignment.x_start, alignment.y_start) == (0, 0)
+        assert alignment.score == aligner.score_global(x[:alignment.x_end], y[:alignment.y_end])[0]
+
+
+def test_pairwise_aligner_extension_gap_across_seed_point():
+    scoring = Scoring.from_scores(-2, -1, match_score=4, mismatch_score=-1)
+    aligner = PairwiseAligner(scoring)
+    # Both halves meet inside of one deletion, which is opened only once
+    alignment = aligner.calculate_extension(b"CC", b"ACGGCC", 1, 3, 13)
+    assert alignment.operations == [Match(), Del(), Del(), Match()]
+    assert alignment.score == alignment.rescore(b"CC", b"ACGGCC", scoring) == 4
+
+    rng = random.Random(13)
+    for _ in range(300):
+        x = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(0, 12)))
+        y = bytes(rng.choice(b"ACGT") for _ in range(rng.randrange(0, 12)))
+        x_seed, y_seed = rng.randrange(0, len(x) + 1), rng.randrange(0, len(y) + 1)
+        alignment = aligner.calculate_extension(x, y, x_seed, y_seed, rng.randrange(0, 20))
+        assert alignment.score == alignment.rescore(x, y, scoring), (x, y, x_seed, y_seed)
+
+
+def test_pairwise_aligner_extension_errors():
+    aligner = PairwiseAligner(Scoring.from_scores(-2, -1, match_score=1, mismatch_score=-1))
+    try:
+        aligner.calculate_extension(b"ACGT", b"ACGT", 2, 0, x_drop=5, seed_len=3)
+    except ValueError as e:
+        assert "seed is out of the sequences" in str(e)
+    else:
+        assert False
+    try:
+        aligner.calculate_extension(b"ACGT", b"ACGT", 0, 0, x_drop=-1)
+    except ValueError as e:
+        assert "x_drop can't be negative" in str(e)
+    else:
+        assert False
+
+
+def test_parse_cigar():
+    assert parse_cigar("3S10M2I1D5=1X2N1P4H") == [
+        (3, 'S'), (10, 'M'), (2, 'I'), (1, 'D'), (5, '='), (1, 'X'), (2, 'N'), (1, 'P'), (4, 'H'),
+    ]
+    assert parse_cigar("") == []
+    assert parse_cigar("*") == []
+    try:
+        parse_cigar("3Q")
+    except ValueError as e:
+        assert "Incorrect CIGAR operation 'Q'" in str(e)
+    else:
+        assert False
+    try:
+        parse_cigar("M")
+    except ValueError as e:
+        assert "must have a positive length" in str(e)
+    else:
+        assert False
+    try:
+        parse_cigar("0M")
+    except ValueError as e:
+        assert "must have a positive length" in str(e)
+    else:
+        assert False
+    try:
+        parse_cigar("3M4")
+    except ValueError as e:
+        assert "can't end with a length" in str(e)
+    else:
+        assert False
+
+
+def test_alignment_from_cigar():
+    x = b"TTACGTAACGTTT"
+    y = b"GGGACGTACGAGGG"
+    alignment = Alignment.from_cigar("2S4M1I2M1D1M3S", 0, 3, len(x), len(y), x=x, y=y)
+    assert alignment.mode == 'local'
+    assert (alignment.x_start, alignment.x_end) == (2, 10)
+    assert (alignment.y_start, alignment.y_end) == (3, 11)
+    assert alignment.score == 0
+    assert alignment.operations == [
+        Match(), Match(), Match(), Match(), Ins(), Match(), Match(), Del(), Subst(),
+    ]
+    assert alignment.pretty(x, y, 100).splitlines()[:3] == [
+        "TT   ACGTAAC-GTTT   ",
+        "     ||||+||x\\      ",
+        "  GGGACGT-ACGA   GGG",
+    ]
+
+    # Without sequences M can't be told apart from a substitution
+    alignment = Alignment.from_cigar("4M2N3=1X", 0, 0, 8, 10)
+    assert alignment.operations == [*repeat(Match(), 4), Del(), Del(), *repeat(Match(), 3), Subst()]
+    assert (alignment.x_end, alignment.y_end) == (8, 10)
+
+
+def test_alignment_from_cigar_custom():
+    x = b"GGGGGGACGTACGTACGT"
+    y = b"AAAAACGTACGTACGTAAAA"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1).xclip(-5).yclip(0)
+    expected = PairwiseAligner(scoring).calculate_custom(x, y)
+    alignment = Alignment.from_cigar(
+        expected.cigar(False), 0, expected.y_start, len(x), len(y), mode='custom', score=expected.score,
+    )
+    assert alignment.operations == expected.operations
+    assert alignment.path() == expected.path()
+    assert alignment.pretty(x, y, 100) == expected.pretty(x, y, 100)
+
+
+def test_alignment_from_cigar_errors():
+    try:
+        Alignment.from_cigar("5M", 0, 0, 4, 10)
+    except ValueError as e:
+        assert "out of the sequences" in str(e)
+    else:
+        assert False
+    try:
+        Alignment.from_cigar("3M2D", 0, 0, 3, 4)
+    except ValueError as e:
+        assert "out of the sequences" in str(e)
+    else:
+        assert False
+    try:
+        Alignment.from_cigar("2M1S2M", 0, 0, 10, 10)
+    except ValueError as e:
+        assert "only allowed at the ends" in str(e)
+    else:
+        assert False
+    try:
+        Alignment.from_cigar("2M", 0, 0, 2, 2, mode='banded')
+    except ValueError as e:
+        assert "can't be used as the mode" in str(e)
+    else:
+        assert False
+
+
+def test_alignment_statistics():
+    alignment = Alignment(
+        score=5,
+        x_start=2,
+        y_start=0,
+        x_end=10,
+        y_end=9,
+        x_len=12,
+        y_len=10,
+        operations=[Match(), Match(), Ins(), Ins(), Match(), Subst(), Del(), Match(), Del(), Del(), Match(), Ins()],
+        mode='local',
+    )
+    assert alignment.matches == 5
+    assert alignment.mismatches == 1
+    assert alignment.insertions == 3
+    assert alignment.deletions == 3
+    assert alignment.gap_opens == 4
+    assert alignment.aligned_length == 12
+    assert alignment.edit_distance == 7
+    assert round(alignment.x_coverage, 6) == round(8 / 12, 6)
+    assert round(alignment.y_coverage, 6) == round(9 / 10, 6)
+    assert round(alignment.identity(), 6) == round(5 / 12, 6)
+    assert round(alignment.identity('matched'), 6) == round(5 / 6, 6)
+    assert round(alignment.identity('gap_compressed'), 6) == round(5 / 10, 6)
+    assert round(alignment.identity('shortest'), 6) == round(5 / 10, 6)
+    assert round(alignment.identity('x'), 6) == round(5 / 12, 6)
+    assert round(alignment.identity('y'), 6) == round(5 / 10, 6)
+    try:
+        alignment.identity('query')
+    except ValueError as e:
+        assert "can't be used as the identity denominator" in str(e)
+    else:
+        assert False
+
+
+def test_alignment_statistics_ignore_clips():
+    alignment = Alignment(
+        score=5,
+        x_start=0,
+        y_start=5,
+        x_end=4,
+        y_end=10,
+        x_len=5,
+        y_len=10,
+        operations=[Yclip(5), Match(), Subst(), Subst(), Ins(), Del(), Del(), Xclip(1)],
+        mode='custom',
+    )
+    assert alignment.aligned_length == 6
+    assert alignment.gap_opens == 2
+    assert alignment.edit_distance == 5
+    assert round(alignment.identity(), 6) == round(1 / 6, 6)
+
+    empty = Alignment(0, 0, 0, 0, 0, 0, 0, [], mode='local')
+    assert empty.identity() == 0.0
+    assert empty.x_coverage == 0.0
+    # Inconsistent coordinates don't underflow
+    inconsistent = Alignment(0, 3, 3, 2, 2, 5, 5, [], mode='local')
+    assert inconsistent.x_coverage == inconsistent.y_coverage == 0.0
+    assert inconsistent.identity() == 0.0
+
+
+def test_alignment_rescore():
+    x = b"LSPADKTNVKAAWGKVGAHAGEYGAEALERMFLSFPTTKTYFPHF"
+    y = b"LSEGEWQLVLHVWAKVEADVAGHGQDILIRLFKSHPETLEKF"
+    blosum62 = Scoring(-10, -1, 'blosum62')
+    pam250 = Scoring(-10, -1, 'pam250')
+    for mode in ('global', 'semiglobal', 'local'):
+        alignment = getattr(PairwiseAligner(blosum62), f'calculate_{mode}')(x, y)
+        assert alignment.rescore(x, y, blosum62) == alignment.score
+        assert sum(alignment.column_scores(x, y, blosum62)) == alignment.score
+        assert len(alignment.column_scores(x, y, blosum62)) == len(alignment.operations)
+
+    alignment = PairwiseAligner(blosum62).calculate_global(x, y)
+    assert alignment.rescore(x, y, pam250) <= PairwiseAligner(pam250).score_global(x, y)[0]
+
+
+def test_alignment_column_scores():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    alignment = Alignment(
+        score=0,
+        x_start=0,
+        y_start=0,
+        x_end=5,
+        y_end=5,
+        x_len=5,
+        y_len=5,
+        operations=[Match(), Ins(), Ins(), Subst(), Del(), Del(), Match()],
+    )
+    assert alignment.column_scores(b"AGGTA", b"ACCCA", scoring) == [1, -6, -1, -1, -6, -1, 1]
+    try:
+        alignment.rescore(b"AGG", b"ACCCA", scoring)
+    except ValueError as e:
+        assert "out of the sequences" in str(e)
+    else:
+        assert False
+
+
+def test_alignment_rescore_custom_clips():
+    x = b"GGGGGGACGTACGTACGT"
+    y = b"AAAAACGTACGTACGTAAAA"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1).xclip(-5).yclip(0)
+    alignment = PairwiseAligner(scoring).calculate_custom(x, y)
+    assert alignment.column_scores(x, y, scoring) == [0, -5, *repeat(1, 12), 0]
+    assert alignment.rescore(x, y, scoring) == alignment.score
+    assert alignment.rescore(x, y, scoring.xclip(-2)) == alignment.score + 3
+
+
+def _gapped_alignment():
+    return Alignment(
+        score=0,
+        x_start=0,
+        y_start=0,
+        x_end=5,
+        y_end=5,
+        x_len=5,
+        y_len=5,
+        operations=[Match(), Ins(), Ins(), Subst(), Del(), Del(), Match()],
+    )
+
+
+def test_alignment_projection():
+    alignment = _gapped_alignment()
+    assert [alignment.x_to_y(i) for i in range(5)] == [0, None, None, 1, 4]
+    assert [alignment.y_to_x(j) for j in range(5)] == [0, 3, None, None, 4]
+    try:
+        alignment.x_to_y(5)
+    except ValueError as e:
+        assert "pos is out of x" in str(e)
+    else:
+        assert False
+
+    x = b"GGGGGGACGTACGTACGT"
+    y = b"AAAAACGTACGTACGTAAAA"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1).xclip(-5).yclip(0)
+    alignment = PairwiseAligner(scoring).calculate_custom(x, y)
+    assert alignment.x_to_y(0) is None
+    assert alignment.x_to_y(6) == 4
+    assert alignment.y_to_x(15) == 17
+
+
+def test_alignment_slice_x():
+    alignment = _gapped_alignment()
+    sliced = alignment.slice_x(1, 4)
+    assert sliced.operations == [Ins(), Ins(), Subst()]
+    assert (sliced.x_start, sliced.y_start, sliced.x_end, sliced.y_end) == (1, 1, 4, 2)
+    assert sliced.mode == 'local'
+
+    sliced = alignment.slice_x(3, 10)
+    assert sliced.operations == [Subst(), Del(), Del(), Match()]
+    assert (sliced.x_start, sliced.y_start, sliced.x_end, sliced.y_end) == (3, 1, 5, 5)
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    assert sliced.rescore(b"AGGTA", b"ACCCA", scoring) == -1 - 7 + 1
+
+    try:
+        alignment.slice_x(1, 3).slice_x(0, 1)
+    except ValueError as e:
+        assert "doesn't overlap" in str(e)
+    else:
+        assert False
+    try:
+        alignment.slice_x(2, 2)
+    except ValueError as e:
+        assert "start must be less than end" in str(e)
+    else:
+        assert False
+
+
+def test_alignment_reverse():
+    x = b"ACCGTGGATGGGCGCGATATTAGC"
+    y = b"GGTACCGTGGACTGCGAGCA"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    alignment = PairwiseAligner(scoring).calculate_local(x, y)
+    reversed_alignment = alignment.reverse()
+    assert reversed_alignment.operations == alignment.operations[::-1]
+    assert reversed_alignment.x_start == len(x) - alignment.x_end
+    assert reversed_alignment.reverse().operations == alignment.operations
+    assert reversed_alignment.rescore(x[::-1], y[::-1], scoring) == alignment.score
+    for i in range(len(x)):
+        j = alignment.x_to_y(i)
+        expected = None if j is None else len(y) - 1 - j
+        assert reversed_alignment.x_to_y(len(x) - 1 - i) == expected
+
+    alignment = Alignment(0, 0, 0, 2, 2, 2, 2, [Match(), Match()])
+    assert (alignment.reverse().x_start, alignment.reverse().y_start) == (0, 0)
+    reversed_alignment = Alignment(0, 0, 0, 3, 2, 2, 2, [Match(), Match()]).reverse()
+    assert (reversed_alignment.x_start, reversed_alignment.x_end) == (0, 2)
+
+
+def test_alignment_format_emboss():
+    alignment = _gapped_alignment()
+    assert alignment.format(b"AGGTA", b"ACCCA", width=4) == (
+        "# 1: x\n"
+        "# 2: y\n"
+        "# Length: 7\n"
+        "# Identity:   2/7 (28.6%)\n"
+        "# Similarity: 2/7 (28.6%)\n"
+        "# Gaps:       4/7 (57.1%)\n"
+        "# Score: 0\n"
+        "\n"
+        "x 1 AGGT 4\n"
+        "    |  .\n"
+        "y 1 A--C 2\n"
+        "\n"
+        "x 5 --A 5\n"
+        "      |\n"
+        "y 3 CCA 5\n"
+    )
+
+
+def test_alignment_format_blast():
+    x = b"LSPADKTNVKAAWGKV"
+    y = b"LSEGEWQLVLHVWAKV"
+    scoring = Scoring(-10, -1, 'blosum62')
+    alignment = PairwiseAligner(scoring).calculate_global(x, y)
+    assert alignment.format(x, y, style='blast', scoring=scoring, x_name='HBA', y_name='MYG') == (
+        " Score = 22, Identities = 6/16 (38%), Positives = 7/16 (44%), Gaps = 0/16 (0%)\n"
+        "\n"
+        "HBA  1   LSPADKTNVKAAWGKV  16\n"
+        "         LS  +   V   W KV\n"
+        "MYG  1   LSEGEWQLVLHVWAKV  16\n"
+    )
+    without_scoring = alignment.format(x, y, style='blast')
+    assert "Positives = 6/16" in without_scoring
+    assert without_scoring.splitlines()[2].startswith("Query  1")
+
+
+def test_alignment_format_errors():
+    alignment = _gapped_alignment()
+    try:
+        alignment.format(b"AGGTA", b"ACCCA", width=0)
+    except ValueError as e:
+        assert "width must be positive" in str(e)
+    else:
+        assert False
+    try:
+        alignment.format(b"AGGTA", b"ACCCA", style="fasta")
+    except ValueError as e:
+        assert "can't be used as the style" in str(e)
+    else:
+        assert False
+    try:
+        alignment.format(b"AGG", b"ACCCA")
+    except ValueError as e:
+        assert "out of the sequences" in str(e)
+    else:
+        assert False
+
+
+def test_alignment_operation_pickle():
+    operations = [Match(), Subst(), Del(), Ins(), Xclip(3), Yclip(5)]
+    assert pickle.loads(pickle.dumps(operations)) == operations
+    assert copy.deepcopy(operations) == operations
+
+
+def test_alignment_pickle_and_copy():
+    x = b"GGGGGGACGTACGTACGT"
+    y = b"AAAAACGTACGTACGTAAAA"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1).xclip(-5).yclip(0)
+    alignment = PairwiseAligner(scoring).calculate_custom(x, y)
+    restored = pickle.loads(pickle.dumps(alignment))
+    assert restored == alignment
+    assert hash(restored) == hash(alignment)
+    assert restored.operations == alignment.operations
+    assert restored.mode == 'custom'
+    assert copy.copy(alignment) == alignment
+    assert copy.deepcopy(alignment) == alignment
+    assert alignment != alignment.reverse()
+    assert len({alignment, restored, alignment.reverse()}) == 2
+
+
+def test_scoring_pickle_and_equality():
+    scorings = [
+        Scoring(-10, -1, 'blosum62').with_clip(xclip_prefix=-3),
+        Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1).yclip(0),
+        Scoring.from_function(-5, -1, lambda a, b: 2 if a == b else -3),
+    ]
+    for scoring in scorings:
+        restored = pickle.loads(pickle.dumps(scoring))
+        assert restored == scoring
+        assert hash(restored) == hash(scoring)
+        assert repr(restored) == repr(scoring)
+        assert copy.copy(scoring) == scoring
+        assert copy.deepcopy(scoring) == scoring
+
+    x, y = b"ACCGTGGATGGGCGCGATATTAGC", b"GGTACCGTGGACTGCGAGCA"
+    restored = pickle.loads(pickle.dumps(scorings[2]))
+    assert PairwiseAligner(restored).calculate_local(x, y) == PairwiseAligner(scorings[2]).calculate_local(x, y)
+
+    assert Scoring(-10, -1, 'blosum62') == Scoring(-10, -1, 'blosum62')
+    assert Scoring(-10, -1, 'blosum62') != Scoring(-10, -1, 'pam250')
+    assert Scoring(-10, -1, 'blosum62') != Scoring(-10, -2, 'blosum62')
+    assert Scoring.from_scores(-5, -1, 1, -1) == Scoring.from_scores(-5, -1, 1, -1)
+    table_scoring = Scoring.from_function(-5, -1, lambda a, b: 1 if a == b else -1)
+    assert table_scoring == Scoring.from_function(-5, -1, lambda a, b: 1 if a == b else -1)
+    assert len({table_scoring, Scoring.from_function(-5, -1, lambda a, b: 1 if a == b else -1)}) == 1
+    # Scorings are compared by how they are defined, not by all of their scores
+    assert Scoring.from_scores(-5, -1, 1, -1) != table_scoring
+
+
+def test_alignment_operation_singletons():
+    alignment = _gapped_alignment()
+    operations = alignment.operations
+    assert operations[0] is operations[-1] is MATCH
+    assert operations[1] is operations[2] is INS
+    assert operations[3] is SUBST
+    assert operations[4] is DEL
+    assert alignment.path()[0][2] is MATCH
+    assert MATCH == Match()
+    assert [o.code for o in (MATCH, SUBST, DEL, INS, Xclip(2), Yclip(3))] == ['=', 'X', 'D', 'I', 'x', 'y']
+
+
+def test_alignment_operation_codes():
+    alignment = _gapped_alignment()
+    assert alignment.operation_codes == b'=IIXDD='
+    assert list(memoryview(alignment.operation_codes)) == [ord(o.code) for o in alignment.operations]
+
+    x = b"GGGGGGACGTACGTACGT"
+    y = b"AAAAACGTACGTACGTAAAA"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1).xclip(-5).yclip(0)
+    alignment = PairwiseAligner(scoring).calculate_custom(x, y)
+    assert alignment.operation_codes == b'yx' + b'=' * 12 + b'y'
+
+
+def test_alignment_runs():
+    alignment = _gapped_alignment()
+    assert alignment.runs() == [(MATCH, 1), (INS, 2), (SUBST, 1), (DEL, 2), (MATCH, 1)]
+    assert alignment.runs()[1][0] is INS
+
+    x = b"GGGGGGACGTACGTACGT"
+    y = b"AAAAACGTACGTACGTAAAA"
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1).xclip(-5).yclip(0)
+    alignment = PairwiseAligner(scoring).calculate_custom(x, y)
+    assert alignment.runs() == [(Yclip(4), 1), (Xclip(6), 1), (MATCH, 12), (Yclip(4), 1)]
+    assert sum(count for _, count in alignment.runs()) == len(alignment.operations)
+
+
+def test_scoring_with_gaps():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    asymmetric = scoring.with_gaps(del_gap_open=-2, ins_gap_extend=-3)
+    assert (asymmetric.gap_open, asymmetric.gap_extend) == (-5, -1)
+    assert (asymmetric.ins_gap_open, asymmetric.ins_gap_extend) == (-5, -3)
+    assert (asymmetric.del_gap_open, asymmetric.del_gap_extend) == (-2, -1)
+    assert (scoring.ins_gap_open, scoring.del_gap_extend) == (-5, -1)
+    assert asymmetric != scoring
+    assert "ins_gap_extend=-3" in repr(asymmetric)
+    assert pickle.loads(pickle.dumps(asymmetric)) == asymmetric
+    try:
+        scoring.with_gaps(del_gap_open=1)
+    except ValueError as e:
+        assert "del_gap_open can't be positive" in str(e)
+    else:
+        assert False
+
+    long_gap = scoring.with_long_gap(-20, 0)
+    assert long_gap.long_gap == (-20, 0)
+    assert scoring.long_gap is None
+    assert "long_gap=(-20, 0)" in repr(long_gap)
+    assert pickle.loads(pickle.dumps(long_gap)) == long_gap
+    try:
+        scoring.with_long_gap(-20, 1)
+    except ValueError as e:
+        assert "gap_extend can't be positive" in str(e)
+    else:
+        assert False
+
+
+def test_aligner_asymmetric_gaps():
+    x = b"ACGTCAGTCAGCATGCATGC"
+    y = b"ACGTCAGTCATTTGCATGCATGC"
+    scoring = Scoring.from_scores(-5, -1, match_score=2, mismatch_score=-3)
+    assert PairwiseAligner(scoring).calculate_global(x, y).score == 40 - 8
+
+    cheap_deletions = scoring.with_gaps(del_gap_open=-1)
+    alignment = PairwiseAligner(cheap_deletions).calculate_global(x, y)
+    assert alignment.score == 40 - 4
+    assert alignment.deletions == 3
+    assert alignment.rescore(x, y, cheap_deletions) == alignment.score
+
+    # Swapping the sequences turns deletions into insertions
+    cheap_insertions = scoring.with_gaps(ins_gap_open=-1)
+    assert PairwiseAligner(cheap_insertions).calculate_global(y, x).score == 40 - 4
+    assert PairwiseAligner(cheap_deletions).calculate_global(y, x).score == 40 - 8
+
+
+def test_aligner_asymmetric_gaps_consistency():
+    rng = random.Random(20)
+    scoring = Scoring.from_scores(-4, -1, match_score=2, mismatch_score=-3).with_gaps(
+        ins_gap_open=-7, ins_gap_extend=-2, del_gap_open=-1, del_gap_extend=-1,
+    )
+    aligner = PairwiseAligner(scoring)
+    for _ in range(30):
+        x = bytes(rng.choice(b"ACGT") for _ in range(rng.randint(1, 40)))
+        y = bytes(rng.choice(b"ACGT") for _ in range(rng.randint(1, 40)))
+        for mode in ('global', 'semiglobal', 'local'):
+            alignment = getattr(aligner, f'calculate_{mode}')(x, y)
+            assert getattr(aligner, f'score_{mode}')(x, y)[0] == alignment.score
+            assert alignment.rescore(x, y, scoring) == alignment.score
+        assert aligner.calculate_local_simd(x, y).score == aligner.calculate_local(x, y).score
+
+    clipped = scoring.xclip(-3).yclip(-2)
+    aligner = PairwiseAligner(clipped)
+    for _ in range(30):
+        x = bytes(rng.choice(b"ACGT") for _ in range(rng.randint(1, 40)))
+        y = bytes(rng.choice(b"ACGT") for _ in range(rng.randint(1, 40)))
+        alignment = aligner.calculate_custom(x, y)
+        assert alignment.rescore(x, y, clipped) == alignment.score
+
+
+def test_aligner_long_gaps():
+    x = b"ACGTCAGTCAGCATGCATGC"
+    y = b"ACGTCAGTCATTTTTTTTTTGCATGCATGC"
+    scoring = Scoring.from_scores(-4, -2, match_score=2, mismatch_score=-3)
+    assert PairwiseAligner(scoring).calculate_global(x, y).score == 40 - 24
+
+    long_gap = scoring.with_long_gap(-10, -1)
+    aligner = PairwiseAligner(long_gap)
+    alignment = aligner.calculate_global(x, y)
+    assert alignment.score == 40 - 20
+    assert alignment.runs() == [(MATCH, 10), (DEL, 10), (MATCH, 10)]
+    assert alignment.column_scores(x, y, long_gap)[10:20] == [-6, -2, -2, -2, -2, -2, -1, -1, -1, -1]
+    assert alignment.rescore(x, y, long_gap) == alignment.score
+    assert aligner.score_global(x, y) == (20, 20, 30)
+    assert aligner.calculate_local(x, y).score == 20
+    assert aligner.calculate_semiglobal(x, y).score == 20
+
+    try:
+        aligner.calculate_custom(x, y)
+    except ValueError as e:
+        assert "Custom mode isn't supported with long gaps" in str(e)
+    else:
+        assert False
+    try:
+        aligner.calculate_extension(x, y, 0, 0, 10)
+    except ValueError as e:
+        assert "X-drop extension isn't supported with long gaps" in str(e)
+    else:
+        assert False
+    try:
+        BandedAligner(long_gap, 4, 4)
+    except ValueError as e:
+        assert "same affine gap penalties" in str(e)
+    else:
+        assert False
+    try:
+        BandedAligner(scoring.with_gaps(del_gap_open=-1), 4, 4)
+    except ValueError as e:
+        assert "same affine gap penalties" in str(e)
+    else:
+        assert False
+
+
+def test_aligner_long_gaps_consistency():
+    rng = random.Random(21)
+    scoring = Scoring.from_scores(-4, -2, match_score=2, mismatch_score=-3).with_gaps(del_gap_open=-3)
+    long_gap = scoring.with_long_gap(-12, -1)
+    for _ in range(30):
+        x = bytes(rng.choice(b"ACGT") for _ in range(rng.randint(1, 40)))
+        y = bytes(rng.choice(b"ACGT") for _ in range(rng.randint(1, 40)))
+        for mode in ('global', 'semiglobal', 'local'):
+            expected = getattr(PairwiseAligner(scoring), f'calculate_{mode}')(x, y)
+            alignment = getattr(PairwiseAligner(long_gap), f'calculate_{mode}')(x, y)
+            assert alignment.score >= expected.score
+            assert alignment.rescore(x, y, long_gap) == alignment.score
+            assert getattr(PairwiseAligner(long_gap), f'score_{mode}')(x, y)[0] == alignment.score
+
+    plain = Scoring.from_scores(-4, -2, match_score=2, mismatch_score=-3)
+    for _ in range(30):
+        x = bytes(rng.choice(b"ACGT") for _ in range(rng.randint(1, 40)))
+        y = bytes(rng.choice(b"ACGT") for _ in range(rng.randint(1, 40)))
+        for mode in ('global', 'semiglobal', 'local'):
+            expected = getattr(PairwiseAligner(plain), f'calculate_{mode}')(x, y)
+            alignment = getattr(PairwiseAligner(plain.with_long_gap(-4, -2)), f'calculate_{mode}')(x, y)
+            assert alignment.score == expected.score
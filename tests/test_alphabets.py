@@ -1,3 +1,6 @@
+import copy
+import pickle
+
 from bioforma.alphabets import Alphabet, RankTransform
 from bioforma.alphabets.dna import (
     make_dna_alphabet,
@@ -106,3 +109,25 @@ def test_rna():
     assert get_rna_complement(b'ACGUN') == b'NACGU'
     assert get_rna_complement(b'GaUuaCA') == b'UGuaAuC'
     assert get_rna_complement(b'AGCUYRWSKMDVHBNZ') == b'ZNVDBHKMSWYRAGCU'
+
+
+def test_alphabet_pickle_and_equality():
+    alphabet = Alphabet(b'ACGT')
+    restored = pickle.loads(pickle.dumps(alphabet))
+    assert restored == alphabet
+    assert hash(restored) == hash(alphabet)
+    assert restored.symbols == b'ACGT'
+    assert alphabet != Alphabet(b'ACGTN')
+    assert alphabet == make_dna_alphabet() & Alphabet(b'ACGTacgt') & Alphabet(b'ACGT')
+    assert copy.copy(alphabet) == alphabet
+    assert copy.deepcopy(alphabet) == alphabet
+
+
+def test_rank_transform_pickle_and_equality():
+    transform = RankTransform(Alphabet(b'TGCA'))
+    restored = pickle.loads(pickle.dumps(transform))
+    assert restored == transform
+    assert hash(restored) == hash(transform)
+    assert restored.ranks == transform.ranks
+    assert transform != RankTransform(Alphabet(b'ACGTN'))
+    assert copy.deepcopy(transform).transform(b'GATTACA') == transform.transform(b'GATTACA')
@@ -0,0 +1,119 @@
+import pickle
+
+from bioforma.alignment import Scoring
+from bioforma.alignment.msa import MultipleAlignment, guide_tree, progressive_align
+from bioforma.alphabets.dna import make_dna_alphabet
+
+
+def _scoring():
+    return Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+
+
+def test_multiple_alignment_init():
+    msa = MultipleAlignment([b'AC-GT', b'ACCGT'])
+    assert len(msa) == 2
+    assert msa.names == ['seq1', 'seq2']
+    assert msa.sequences == [b'AC-GT', b'ACCGT']
+    assert msa.num_columns == 5
+    assert msa[0] == b'AC-GT'
+    assert msa.column(2) == b'-C'
+    assert msa.ungapped(0) == b'ACGT'
+    assert repr(msa) == '<MultipleAlignment: sequences=2, columns=5>'
+    for args in ([],), ([b'ACGT', b'ACG'],), ([b'ACGT'], ['a', 'b']):
+        try:
+            MultipleAlignment(*args)
+        except ValueError:
+            assert True
+        else:
+            assert False
+    try:
+        msa.column(5)
+    except IndexError:
+        assert True
+    else:
+        assert False
+
+
+def test_multiple_alignment_alphabet():
+    msa = MultipleAlignment([b'ACGT', b'AC-T'], alphabet=make_dna_alphabet())
+    msa.validate(make_dna_alphabet())
+    try:
+        MultipleAlignment([b'ACGT', b'ACGU'], alphabet=make_dna_alphabet())
+    except ValueError as e:
+        assert str(e) == 'Sequence seq2 has symbols out of the alphabet'
+    else:
+        assert False
+
+
+def test_multiple_alignment_statistics():
+    msa = MultipleAlignment([b'ACGT-A', b'ACCT-A', b'AGGTTA', b'A-GT--'])
+    assert msa.conservation() == [1.0, 0.5, 0.75, 1.0, 0.25, 0.75]
+    assert msa.gap_fraction() == [0.0, 0.25, 0.0, 0.0, 0.75, 0.25]
+    entropy = msa.entropy()
+    assert entropy[0] == 0.0
+    assert abs(entropy[1] - 0.918296) < 1e-6
+    assert abs(entropy[2] - 0.811278) < 1e-6
+    assert entropy[3:] == [0.0, 0.0, 0.0]
+    assert msa.identity_matrix()[1] == [80.0, 100.0, 60.0, 200 / 3]
+
+
+def test_multiple_alignment_consensus():
+    msa = MultipleAlignment([b'ACGT-A', b'ACCT-A', b'AGGTTA', b'A-GT--'])
+    assert msa.consensus() == b'ACGTA'
+    assert msa.consensus(include_gaps=True) == b'ACGT-A'
+    assert msa.iupac_consensus() == b'ASSTA'
+    assert msa.iupac_consensus(include_gaps=True) == b'ASST-A'
+    assert MultipleAlignment([b'ARN', b'GCN']).iupac_consensus() == b'RVN'
+    try:
+        MultipleAlignment([b'ACGT', b'ACGX']).iupac_consensus()
+    except ValueError:
+        assert True
+    else:
+        assert False
+
+
+def test_multiple_alignment_pickle():
+    msa = MultipleAlignment([b'AC-GT', b'ACCGT'], names=['a', 'b'])
+    assert pickle.loads(pickle.dumps(msa)) == msa
+    assert msa != MultipleAlignment([b'AC-GT', b'ACCGT'])
+
+
+def test_multiple_alignment_output():
+    msa = MultipleAlignment([b'ACG-TAC', b'ACGTTAC', b'ASG--AC'], names=['a', 'bb', 'c'])
+    assert msa.to_fasta(line_width=4) == '>a\nACG-\nTAC\n>bb\nACGT\nTAC\n>c\nASG-\n-AC\n'
+    assert msa.to_clustal(line_width=4) == (
+        'CLUSTAL W multiple sequence alignment\n'
+        '\n'
+        'a     ACG-\n'
+        'bb    ACGT\n'
+        'c     ASG-\n'
+        '      *.*\n'
+        '\n'
+        'a     TAC\n'
+        'bb    TAC\n'
+        'c     -AC\n'
+        '       **\n'
+    )
+
+
+def test_progressive_align():
+    sequences = [b'ACGTACGTAC', b'ACGTTACGTAC', b'ACGACGTAC', b'TTTTACGTAC']
+    msa = progressive_align(sequences, _scoring(), names=['a', 'b', 'c', 'd'], threads=2)
+    assert msa.names == ['a', 'b', 'c', 'd']
+    assert msa.sequences == [b'ACG-TACGTAC', b'ACGTTACGTAC', b'ACG--ACGTAC', b'-TTTTACGTAC']
+    assert [msa.ungapped(i) for i in range(len(msa))] == sequences
+    assert progressive_align([b'ACGT'], _scoring()).sequences == [b'ACGT']
+    for sequences in [], [b'AC-GT']:
+        try:
+            progressive_align(sequences, _scoring())
+        except ValueError:
+            assert True
+        else:
+            assert False
+
+
+def test_guide_tree():
+    sequences = [b'ACGTACGTAC', b'ACGTTACGTAC', b'ACGACGTAC', b'TTTTACGTAC']
+    assert guide_tree(sequences, _scoring(), names=['a', 'b', 'c', 'd']) == \
+        '(((a:0.00000,b:0.00000):0.00000,c:0.00000):0.13889,d:0.13889);'
+    assert guide_tree([b'ACGT'], _scoring()) == 'seq1;'
@@ -0,0 +1,200 @@
+import random
+
+from bioforma.alignment import Match, Subst, Del, Ins, Scoring
+from bioforma.alignment.poa import PoaAligner, PoaAlignment, PoaGraph
+
+
+def test_poa_aligner_init():
+    scoring = Scoring.from_scores(-1, 0, match_score=1, mismatch_score=-1)
+    try:
+        PoaAligner(scoring, b'')
+    except ValueError:
+        assert True
+    else:
+        assert False
+    try:
+        PoaAligner(scoring.with_gaps(del_gap_open=-2), b'ACGT')
+    except ValueError:
+        assert True
+    else:
+        assert False
+    try:
+        PoaAligner(scoring.with_long_gap(-5, 0), b'ACGT')
+    except ValueError:
+        assert True
+    else:
+        assert False
+
+
+def test_poa_global_alignment():
+    scoring = Scoring.from_scores(-1, 0, match_score=1, mismatch_score=-1)
+    aligner = PoaAligner(scoring, b'AAAAAAA')
+    # z differs from x in 3 locations
+    assert aligner.calculate_global(b'AABCBAA').score == 1
+    aligner.calculate_global(b'AABBBAA')
+    aligner.add_to_graph()
+    # z differs from x and y's partial order alignment by 1 base
+    alignment = aligner.calculate_global(b'AABCBAA')
+    assert isinstance(alignment, PoaAlignment)
+    assert alignment.score == 5
+    assert alignment.mode == 'global'
+    assert alignment.x_start == 0
+    assert alignment.x_end == 7
+    assert repr(alignment) == '<PoaAlignment: score=5, x_start=0, x_end=7, mode=global>'
+    assert alignment.operations == [
+        Match(),
+        Match(),
+        Match(),
+        Subst(),
+        Match(),
+        Match(),
+        Match(),
+    ]
+
+
+def test_poa_alignment_path():
+    scoring = Scoring.from_scores(-1, 0, match_score=1, mismatch_score=-1)
+    aligner = PoaAligner(scoring, b'GATTACA')
+    alignment = aligner.calculate_global(b'GATACA')
+    assert alignment.score == 5
+    assert alignment.operations.count(Del()) == 1
+    assert len(alignment.nodes) == 7
+    path = alignment.path()
+    assert path[0] == (0, 0, Match())
+    assert [x for x, _, _ in path if x is not None] == list(range(6))
+    assert sum(1 for x, _, op in path if x is None and op == Del()) == 1
+
+    alignment = aligner.calculate_global(b'GATTTACA')
+    assert alignment.operations.count(Ins()) == 1
+    assert len(alignment.nodes) == 7
+
+
+def test_poa_local_alignment():
+    scoring = Scoring.from_scores(-1, 0, match_score=1, mismatch_score=-1)
+    aligner = PoaAligner(scoring, b'GATTACA')
+    alignment = aligner.calculate_local(b'CCCCTTACACCCC')
+    assert alignment.mode == 'local'
+    assert alignment.score == 5
+    assert alignment.x_start == 4
+    assert alignment.x_end == 9
+    assert alignment.operations == [Match()] * 5
+
+
+def test_poa_add_to_graph():
+    scoring = Scoring.from_scores(-1, 0, match_score=1, mismatch_score=-1)
+    aligner = PoaAligner(scoring, b'GATTACA')
+    try:
+        aligner.add_to_graph()
+    except ValueError:
+        assert True
+    else:
+        assert False
+
+    graph = aligner.graph
+    assert isinstance(graph, PoaGraph)
+    assert graph.node_count == 7
+    assert graph.edge_count == 6
+    assert graph.nodes == b'GATTACA'
+
+    aligner.calculate_global(b'GATTGCA')
+    aligner.add_to_graph()
+    graph = aligner.graph
+    assert graph.node_count == 8
+    assert graph.nodes == b'GATTACAG'
+    assert (0, 1, 2) in graph.edges
+    assert (3, 7, 1) in graph.edges
+    assert (7, 5, 1) in graph.edges
+    assert repr(graph) == '<PoaGraph: node_count=8, edge_count=8>'
+
+
+def test_poa_consensus():
+    scoring = Scoring.from_scores(-4, -2, match_score=2, mismatch_score=-4)
+    sequences = [
+        b'ATTGCCCGTTGGAGAAAACTTG',
+        b'ATTGCCGGTTGGAGAAAACTTG',
+        b'ATTGCCCGTTGGAGAAAACTTG',
+        b'ATTGCCCGTTGCAGAAAACTTG',
+        b'ATTGCCCGTAGGAGAAAACTTG',
+    ]
+    aligner = PoaAligner(scoring, sequences[0])
+    for sequence in sequences[1:]:
+        aligner.calculate_global(sequence)
+        aligner.add_to_graph()
+    assert aligner.consensus() == b'ATTGCCCGTTGGAGAAAACTTG'
+
+
+def test_poa_multiple_alignment():
+    scoring = Scoring.from_scores(-1, 0, match_score=1, mismatch_score=-1)
+    aligner = PoaAligner(scoring, b'AAAAAAA')
+    for sequence in [b'AABBBAA', b'AAAAA', b'AATAAAAG']:
+        aligner.calculate_global(sequence)
+        aligner.add_to_graph()
+    msa = aligner.multiple_alignment(names=['ref', 'b', 'short', 't'])
+    assert msa.names == ['ref', 'b', 'short', 't']
+    assert msa.sequences == [b'AAAAAAA-', b'AABBBAA-', b'AAAAA---', b'AATAAAAG']
+    assert msa.consensus() == b'AAAAAAA'
+
+
+def test_poa_consensus_single_node():
+    scoring = Scoring.from_scores(-2, -1, match_score=1, mismatch_score=-1)
+    aligner = PoaAligner(scoring, b'C')
+    aligner.calculate_global(b'T')
+    aligner.add_to_graph()
+    assert aligner.consensus() in (b'C', b'T')
+    aligner = PoaAligner(scoring, b'C')
+    aligner.calculate_global(b'')
+    aligner.add_to_graph()
+    assert aligner.consensus() == b'C'
+
+
+def test_poa_several_source_nodes():
+    scoring = Scoring.from_scores(-2, -1, match_score=1, mismatch_score=-1)
+    aligner = PoaAligner(scoring, b'ACCCG')
+    # the head substitution adds a second source node to align to
+    for sequence in [b'TCACA', b'GTACCGAT']:
+        aligner.calculate_global(sequence)
+        aligner.add_to_graph()
+    assert aligner.consensus()
+    msa = aligner.multiple_alignment()
+    assert [row.replace(b'-', b'') for row in msa.sequences] == [b'ACCCG', b'TCACA', b'GTACCGAT']
+
+
+def test_poa_random_global_adds():
+    rng = random.Random(11)
+    scoring = Scoring.from_scores(-2, -1, match_score=1, mismatch_score=-1)
+    for _ in range(500):
+        sequences = [
+            bytes(rng.choice(b'ACGT') for _ in range(rng.randint(1, 10)))
+            for _ in range(rng.randint(2, 6))
+        ]
+        aligner = PoaAligner(scoring, sequences[0])
+        for sequence in sequences[1:]:
+            aligner.calculate_global(sequence)
+            aligner.add_to_graph()
+        assert aligner.consensus()
+        msa = aligner.multiple_alignment()
+        assert [row.replace(b'-', b'') for row in msa.sequences] == sequences
+
+
+def test_poa_multiple_alignment_after_head_substitutions():
+    scoring = Scoring.from_scores(-2, -1, match_score=1, mismatch_score=-1)
+    aligner = PoaAligner(scoring, b'ACCCG')
+    for sequence in [b'TCACA', b'GTACCGAT', b'TCCCG', b'ACCCGA']:
+        aligner.calculate_global(sequence)
+        aligner.add_to_graph()
+    assert aligner.multiple_alignment().sequences == [
+        b'-ACCC-G--',
+        b'-TCAC-A--',
+        b'GTACCGAT-',
+        b'-TCCC-G--',
+        b'-ACCC-G-A',
+    ]
+
+
+def test_poa_graph_to_dot():
+    scoring = Scoring.from_scores(-1, 0, match_score=1, mismatch_score=-1)
+    dot = PoaAligner(scoring, b'GAT').graph.to_dot()
+    assert dot.startswith('digraph {')
+    assert '0 [ label = "G" ]' in dot
+    assert '0 -> 1 [ label = "1" ]' in dot
+    assert '1 -> 2 [ label = "1" ]' in dot
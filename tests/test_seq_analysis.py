@@ -1,5 +1,9 @@
+import copy
+import pickle
+
 from bioforma.seq_analysis.gc import gc_content, gc3_content
-from bioforma.seq_analysis.orf import Finder
+from bioforma.seq_analysis.orf import Finder, Orf
+from bioforma.seq_analysis.barcodes import BarcodeCorrector, cluster_umis
 
 
 def test_gc():
@@ -64,3 +68,63 @@ def test_orf():
     assert res[2].start == 6
     assert res[2].end == 30
     assert res[2].offset == 0
+
+
+def test_orf_pickle_and_equality():
+    finder = Finder([b'ATG'], [b'TGA', b'TAG', b'TAA'], 5)
+    orfs = finder.find_all(b'ACGGCTAGAAAAGGCTAGAAAATGAAAAAAGTAAAAAAGGATGCCCGTTTTAGTA')
+    assert pickle.loads(pickle.dumps(orfs)) == orfs
+    orf = Orf(start=0, end=9, offset=1)
+    assert orf == Orf(0, 9, 1)
+    assert orf != Orf(0, 9, 2)
+    assert hash(orf) == hash(copy.copy(orf))
+    assert copy.deepcopy(orf) == orf
+    assert orf.end == 9
+
+
+def test_barcode_corrector():
+    whitelist = [b'AAAACCCC', b'GGGGTTTT', b'ACGTACGT', b'ACGTACGA']
+    corrector = BarcodeCorrector(whitelist)
+    assert len(corrector) == 4
+    assert b'ACGTACGT' in corrector
+    assert repr(corrector) == '<BarcodeCorrector: barcodes=4, metric=hamming, max_dist=1>'
+    assert corrector.correct(b'AAAACCCC') == b'AAAACCCC'
+    assert corrector.correct(b'AAAACCCG') == b'AAAACCCC'
+    # equally close to two whitelist barcodes
+    assert corrector.correct(b'ACGTACGC') is None
+    assert corrector.correct(b'TTTTTTTT') is None
+    assert corrector.correct(b'AAAACCC') is None
+
+    corrector = BarcodeCorrector(whitelist, metric='levenshtein', max_dist=2)
+    assert corrector.correct_many([b'AAACCCC', b'GGGGTTTTA', b'AAAACCCCGG', b'CCCC'], threads=2) == [
+        b'AAAACCCC', b'GGGGTTTT', b'AAAACCCC', None,
+    ]
+    for args in ([],), ([b'ACGT', b'ACG'],), (whitelist, 'cosine'), (whitelist, 'hamming', 8):
+        try:
+            BarcodeCorrector(*args)
+        except ValueError:
+            assert True
+        else:
+            assert False
+
+
+def test_cluster_umis():
+    counts = {b'ACGT': 456, b'TCGT': 2, b'CCGT': 2, b'ACAT': 72, b'ACAG': 1, b'AAAT': 90, b'GGGG': 5}
+    assert cluster_umis(counts) == [
+        [b'ACGT', b'ACAT', b'CCGT', b'TCGT', b'ACAG'],
+        [b'AAAT'],
+        [b'GGGG'],
+    ]
+    assert cluster_umis(counts, method='adjacency', threads=2) == [
+        [b'ACGT', b'CCGT', b'TCGT'],
+        [b'AAAT'],
+        [b'ACAT', b'ACAG'],
+        [b'GGGG'],
+    ]
+    assert cluster_umis({}) == []
+    try:
+        cluster_umis(counts, method='cluster')
+    except ValueError:
+        assert True
+    else:
+        assert False
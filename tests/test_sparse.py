@@ -0,0 +1,111 @@
+from bioforma.alignment import Match, Del, Ins, Scoring, PairwiseAligner
+from bioforma.alignment.sparse import (
+    SparseAlignmentResult,
+    find_kmer_matches,
+    expand_kmer_matches,
+    lcskpp,
+    sdpkpp,
+    sdpkpp_union_lcskpp_path,
+    chain_to_alignment,
+)
+
+
+def test_find_kmer_matches():
+    assert find_kmer_matches(b'ACGTACGATAGGTA', b'TTACGTACGATAGGTATT', 8) == [
+        (0, 2), (1, 3), (2, 4), (3, 5), (4, 6), (5, 7), (6, 8),
+    ]
+    assert find_kmer_matches(b'AAAA', b'CCCC', 2) == []
+    try:
+        find_kmer_matches(b'AAAA', b'AAAA', 0)
+    except ValueError:
+        assert True
+    else:
+        assert False
+
+
+def test_expand_kmer_matches():
+    x = b'ACGTACGATAGGTA'
+    y = b'ACGTACGATTGGTA'
+    matches = find_kmer_matches(x, y, 4)
+    assert matches == [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4), (5, 5), (10, 10)]
+    expanded = expand_kmer_matches(x, y, 4, matches, 1)
+    assert expanded == [(i, i) for i in range(11)]
+    try:
+        expand_kmer_matches(x, y, 4, list(reversed(matches)), 1)
+    except ValueError:
+        assert True
+    else:
+        assert False
+
+
+def test_lcskpp():
+    matches = find_kmer_matches(b'ACGTACGATAGGTA', b'TTACGTACGATAGGTATT', 8)
+    result = lcskpp(matches, 8)
+    assert isinstance(result, SparseAlignmentResult)
+    assert [matches[i] for i in result.path] == matches
+    assert result.score == 14
+    assert repr(result) == '<SparseAlignmentResult: score=14, path=[0, 1, 2, 3, 4, 5, 6]>'
+    assert lcskpp([], 8).path == []
+    for invalid in list(reversed(matches)), [matches[0], matches[0]]:
+        try:
+            lcskpp(invalid, 8)
+        except ValueError:
+            assert True
+        else:
+            assert False
+
+
+def test_sdpkpp():
+    x = b'ACGTACGATAGGTA'
+    y = b'TTACGTACGATAGGTATT'
+    matches = find_kmer_matches(x, y, 8)
+    result = sdpkpp(matches, 8, 1, -1, -1)
+    assert [matches[i] for i in result.path] == matches
+    assert result.score == 14
+    assert sdpkpp_union_lcskpp_path(matches, 8, 1, -1, -1) == list(range(7))
+    try:
+        sdpkpp(matches, 8, 1, 1, -1)
+    except ValueError:
+        assert True
+    else:
+        assert False
+    for function in sdpkpp, sdpkpp_union_lcskpp_path:
+        try:
+            function(list(reversed(matches)), 8, 1, -1, -1)
+        except ValueError:
+            assert True
+        else:
+            assert False
+
+
+def test_chain_to_alignment():
+    x = b'ACGTACGTTTTTGCATGCATCCAGTCAGT'
+    y = b'GGACGTACGTTGCATGCAATCCAGTCAGTGG'
+    k = 5
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    matches = find_kmer_matches(x, y, k)
+    result = lcskpp(matches, k)
+    alignment = chain_to_alignment(x, y, matches, result.path, k, scoring)
+    assert alignment.mode == 'local'
+    assert alignment.x_start == 0
+    assert alignment.y_start == 2
+    assert alignment.x_end == len(x)
+    assert alignment.y_end == len(y) - 2
+    assert alignment.x_len == len(x)
+    assert alignment.y_len == len(y)
+    assert alignment.operations == (
+        [Match()] * 7 + [Ins()] * 3 + [Match()] * 8 + [Del()] + [Match()] * 11
+    )
+    assert alignment.score == 26 - 8 - 6
+    assert alignment.score <= PairwiseAligner(scoring).calculate_local(x, y).score
+
+
+def test_chain_to_alignment_errors():
+    scoring = Scoring.from_scores(-5, -1, match_score=1, mismatch_score=-1)
+    for matches, path in [([(0, 0)], []), ([(0, 0)], [1]), ([(9, 0)], [0]), ([(4, 4), (0, 0)], [0, 1])]:
+        try:
+            chain_to_alignment(b'ACGTACGT', b'ACGTACGT', matches, path, 4, scoring)
+        except ValueError:
+            assert True
+        else:
+            assert False